@@ -1,25 +1,176 @@
-use crate::engine::{tensor::EngineTensor, unit::UnitCompatible, EngineError};
+use std::cell::RefCell;
 
-use super::{NodeKey, ComputationGraphError};
+use crate::{engine::{tensor::EngineTensor, unit::UnitCompatible, ConvParams, EmbeddingBagMode, EngineError, Reduction}, engine_impl::util::{adaptive_pool_2d_shape, attention_shape, concat_shape, conv_shape, conv_transpose_shape, embedding_bag_shape, embedding_shape, err_if_dimension_mismatch, err_if_invalid_index_shape, err_if_too_few_dimensions, flatten_shape, index_select_shape, narrow_shape, permute_shape, pool_2d_shape, reduced_axes_shape, reshape_shape, stack_shape, transpose_dims, unflatten_shape}, helper::{shape, Rng, Shape, VarArrayCompatible}};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+use super::{scan::Scan, NodeKey, ComputationGraphError};
+
+#[derive(Debug)]
 pub enum Edge<T: UnitCompatible> {
     Root,
 
     Abs(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     Neg(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
 
+    Relu(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    LeakyRelu(NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Sigmoid(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Silu(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Gelu(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    GeluTanh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Mish(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Selu(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Hardswish(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Elu(NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Softplus(NodeKey, f64, f64, fn(&dyn EngineTensor<Unit = T>, f64, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Hardtanh(NodeKey, f64, f64, fn(&dyn EngineTensor<Unit = T>, f64, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, weight
+    Prelu(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    Sqrt(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Rsqrt(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Reciprocal(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Exp(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Expm1(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Log(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Log1p(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Sin(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Cos(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Tan(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Sinh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Cosh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Tanh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Asin(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Acos(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Atan(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Asinh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Acosh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Atanh(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Floor(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Ceil(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Round(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Sign(NodeKey, fn(&dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
     AddScalar(T, NodeKey, fn(T, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     SubScalarLH(T, NodeKey, fn(T, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     SubScalarRH(NodeKey, T, fn(&dyn EngineTensor<Unit = T>, T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     MulScalar(T, NodeKey, fn(T, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     DivScalarLH(T, NodeKey, fn(T, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     DivScalarRH(NodeKey, T, fn(&dyn EngineTensor<Unit = T>, T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    PowScalar(NodeKey, T, fn(&dyn EngineTensor<Unit = T>, T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
 
     Add(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     Sub(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     Mul(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
     Div(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Pow(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Maximum(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Minimum(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Eq(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Ne(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Lt(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Le(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Gt(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Ge(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, p, training, rng
+    //The rng is seeded when the node is created and advanced on every evaluation, so reruns drop different units
+    Dropout(NodeKey, f64, bool, RefCell<Rng>, fn(&dyn EngineTensor<Unit = T>, f64, bool, &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Cond, a, b
+    Where(NodeKey, NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, min, max
+    Clamp(NodeKey, Option<T>, Option<T>, fn(&dyn EngineTensor<Unit = T>, Option<T>, Option<T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, mask, value
+    MaskedFill(NodeKey, NodeKey, T, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    MatMul(NodeKey, NodeKey, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, kernel, bias, params
+    Conv1d(NodeKey, NodeKey, Option<NodeKey>, ConvParams<1>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Option<&dyn EngineTensor<Unit = T>>, ConvParams<1>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Conv2d(NodeKey, NodeKey, Option<NodeKey>, ConvParams<2>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Option<&dyn EngineTensor<Unit = T>>, ConvParams<2>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Conv3d(NodeKey, NodeKey, Option<NodeKey>, ConvParams<3>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Option<&dyn EngineTensor<Unit = T>>, ConvParams<3>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, kernel, bias, params, output_padding
+    ConvTranspose2d(NodeKey, NodeKey, Option<NodeKey>, ConvParams<2>, [usize; 2], fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Option<&dyn EngineTensor<Unit = T>>, ConvParams<2>, [usize; 2]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, kernel, stride, padding, dilation
+    MaxPool2d(NodeKey, (usize, usize), (usize, usize), (usize, usize), (usize, usize), fn(&dyn EngineTensor<Unit = T>, (usize, usize), (usize, usize), (usize, usize), (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, kernel, stride, padding, dilation, count include pad
+    AvgPool2d(NodeKey, (usize, usize), (usize, usize), (usize, usize), (usize, usize), bool, fn(&dyn EngineTensor<Unit = T>, (usize, usize), (usize, usize), (usize, usize), (usize, usize), bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, indices, output size
+    MaxUnpool2d(NodeKey, Box<dyn EngineTensor<Unit = usize>>, (usize, usize), fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = usize>, (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, output size
+    AdaptiveAvgPool2d(NodeKey, (usize, usize), fn(&dyn EngineTensor<Unit = T>, (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    AdaptiveMaxPool2d(NodeKey, (usize, usize), fn(&dyn EngineTensor<Unit = T>, (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, weight, bias, eps
+    BatchNormNoRunning(NodeKey, NodeKey, NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, running mean, running var, weight, bias, eps
+    BatchNormRunning(NodeKey, NodeKey, NodeKey, NodeKey, NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, weight, bias, normalized shape, eps
+    LayerNorm(NodeKey, NodeKey, NodeKey, Shape, f64, fn(&dyn EngineTensor<Unit = T>, &Shape, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, weight, bias, groups, eps
+    GroupNorm(NodeKey, NodeKey, NodeKey, usize, f64, fn(&dyn EngineTensor<Unit = T>, usize, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    InstanceNorm(NodeKey, NodeKey, NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, weight, normalized shape, eps
+    RmsNorm(NodeKey, NodeKey, Shape, f64, fn(&dyn EngineTensor<Unit = T>, &Shape, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, running statistic, momentum
+    BatchNormRunningMean(NodeKey, NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    BatchNormRunningVar(NodeKey, NodeKey, f64, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, axes, keep dims
+    Sum(NodeKey, Box<[usize]>, bool, fn(&dyn EngineTensor<Unit = T>, &[usize], bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Mean(NodeKey, Box<[usize]>, bool, fn(&dyn EngineTensor<Unit = T>, &[usize], bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Max(NodeKey, Box<[usize]>, bool, fn(&dyn EngineTensor<Unit = T>, &[usize], bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Min(NodeKey, Box<[usize]>, bool, fn(&dyn EngineTensor<Unit = T>, &[usize], bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Product(NodeKey, Box<[usize]>, bool, fn(&dyn EngineTensor<Unit = T>, &[usize], bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, axis
+    Softmax(NodeKey, usize, fn(&dyn EngineTensor<Unit = T>, usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    LogSoftmax(NodeKey, usize, fn(&dyn EngineTensor<Unit = T>, usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Inputs, axis
+    Concat(Box<[NodeKey]>, usize, fn(&[&dyn EngineTensor<Unit = T>], usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Stack(Box<[NodeKey]>, usize, fn(&[&dyn EngineTensor<Unit = T>], usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, dims
+    Permute(NodeKey, Box<[usize]>, fn(&dyn EngineTensor<Unit = T>, &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Transpose(NodeKey, usize, usize, fn(&dyn EngineTensor<Unit = T>, usize, usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Reshape(NodeKey, Shape, fn(&dyn EngineTensor<Unit = T>, &Shape) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, start, end
+    Flatten(NodeKey, usize, usize, fn(&dyn EngineTensor<Unit = T>, usize, usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, axis, sizes
+    Unflatten(NodeKey, usize, Box<[usize]>, fn(&dyn EngineTensor<Unit = T>, usize, &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, axis, start, length
+    Narrow(NodeKey, usize, usize, usize, fn(&dyn EngineTensor<Unit = T>, usize, usize, usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Query, key, value, mask, causal offset
+    ScaledDotProductAttention(NodeKey, NodeKey, NodeKey, Option<NodeKey>, Option<usize>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Option<&dyn EngineTensor<Unit = T>>, Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, axis, index, (src)
+    //Indices aren't units of the graph so they are held by the edge
+    IndexSelect(NodeKey, usize, Box<dyn EngineTensor<Unit = usize>>, fn(&dyn EngineTensor<Unit = T>, usize, &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Gather(NodeKey, usize, Box<dyn EngineTensor<Unit = usize>>, fn(&dyn EngineTensor<Unit = T>, usize, &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    Scatter(NodeKey, usize, Box<dyn EngineTensor<Unit = usize>>, NodeKey, fn(&dyn EngineTensor<Unit = T>, usize, &dyn EngineTensor<Unit = usize>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    ScatterAdd(NodeKey, usize, Box<dyn EngineTensor<Unit = usize>>, NodeKey, fn(&dyn EngineTensor<Unit = T>, usize, &dyn EngineTensor<Unit = usize>, &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Weight, ids, padding index, max norm
+    Embedding(NodeKey, Box<dyn EngineTensor<Unit = usize>>, Option<usize>, Option<f64>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = usize>, Option<usize>, Option<f64>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Weight, ids, mode, padding index
+    EmbeddingBag(NodeKey, Box<dyn EngineTensor<Unit = usize>>, EmbeddingBagMode, Option<usize>, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = usize>, EmbeddingBagMode, Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Input, target
+    MseLoss(NodeKey, NodeKey, Reduction, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    L1Loss(NodeKey, NodeKey, Reduction, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    BinaryCrossEntropyWithLogits(NodeKey, NodeKey, Reduction, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, target, delta
+    HuberLoss(NodeKey, NodeKey, f64, Reduction, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64, Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+    //Input, target, label smoothing
+    CrossEntropy(NodeKey, NodeKey, f64, Reduction, fn(&dyn EngineTensor<Unit = T>, &dyn EngineTensor<Unit = T>, f64, Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>),
+
+    //Initial carry, captures, body
+    Scan(NodeKey, Box<[NodeKey]>, Box<Scan<T>>),
 }
 
 impl<T: UnitCompatible> Edge<T> {
@@ -39,9 +190,60 @@ impl<T: UnitCompatible> Edge<T> {
         match self {
             Edge::Root => Err(ComputationGraphError::RootNodeNotComputed()),
             Edge::Abs(a_key, op) |  
-            Edge::Neg(a_key, op) => {
+            Edge::Neg(a_key, op) |
+            Edge::Relu(a_key, op) |
+            Edge::Sigmoid(a_key, op) |
+            Edge::Silu(a_key, op) |
+            Edge::Gelu(a_key, op) |
+            Edge::GeluTanh(a_key, op) |
+            Edge::Mish(a_key, op) |
+            Edge::Selu(a_key, op) |
+            Edge::Hardswish(a_key, op) |
+            Edge::Sqrt(a_key, op) |
+            Edge::Rsqrt(a_key, op) |
+            Edge::Reciprocal(a_key, op) |
+            Edge::Exp(a_key, op) |
+            Edge::Expm1(a_key, op) |
+            Edge::Log(a_key, op) |
+            Edge::Log1p(a_key, op) |
+            Edge::Sin(a_key, op) |
+            Edge::Cos(a_key, op) |
+            Edge::Tan(a_key, op) |
+            Edge::Sinh(a_key, op) |
+            Edge::Cosh(a_key, op) |
+            Edge::Tanh(a_key, op) |
+            Edge::Asin(a_key, op) |
+            Edge::Acos(a_key, op) |
+            Edge::Atan(a_key, op) |
+            Edge::Asinh(a_key, op) |
+            Edge::Acosh(a_key, op) |
+            Edge::Atanh(a_key, op) |
+            Edge::Floor(a_key, op) |
+            Edge::Ceil(a_key, op) |
+            Edge::Round(a_key, op) |
+            Edge::Sign(a_key, op) => {
                 op(resolve(*a_key)?).map_err(|e| ComputationGraphError::from(e))
             },
+            Edge::LeakyRelu(a_key, alpha, op) |
+            Edge::Elu(a_key, alpha, op) => {
+                op(resolve(*a_key)?, *alpha).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Softplus(a_key, x, y, op) |
+            Edge::Hardtanh(a_key, x, y, op) => {
+                op(resolve(*a_key)?, *x, *y).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Dropout(a_key, p, training, rng, op) => {
+                op(resolve(*a_key)?, *p, *training, &mut rng.borrow_mut()).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Clamp(a_key, min, max, op) => {
+                op(resolve(*a_key)?, *min, *max).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Where(cond_key, a_key, b_key, op) => {
+                op(resolve(*cond_key)?, resolve(*a_key)?, resolve(*b_key)?).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::MaskedFill(a_key, mask_key, value, op) => {
+                op(resolve(*a_key)?, resolve(*mask_key)?, *value).map_err(|e| ComputationGraphError::from(e))
+            },
             Edge::AddScalar(s, a_key, op) |
             Edge::SubScalarLH(s, a_key, op) |
             Edge::MulScalar(s, a_key, op) |
@@ -49,20 +251,353 @@ impl<T: UnitCompatible> Edge<T> {
                 op(*s, resolve(*a_key)?).map_err(|e| ComputationGraphError::from(e))
             },
             Edge::SubScalarRH(a_key, s, op) |
-            Edge::DivScalarRH(a_key, s, op) => {
+            Edge::DivScalarRH(a_key, s, op) |
+            Edge::PowScalar(a_key, s, op) => {
                     op(resolve(*a_key)?, *s).map_err(|e| ComputationGraphError::from(e))
             }
             Edge::Add(a_key, b_key, op) |
             Edge::Sub(a_key, b_key, op) |
             Edge::Mul(a_key, b_key, op) |
-            Edge::Div(a_key, b_key, op) => {
+            Edge::Div(a_key, b_key, op) |
+            Edge::Pow(a_key, b_key, op) |
+            Edge::Maximum(a_key, b_key, op) |
+            Edge::Minimum(a_key, b_key, op) |
+            Edge::Eq(a_key, b_key, op) |
+            Edge::Ne(a_key, b_key, op) |
+            Edge::Lt(a_key, b_key, op) |
+            Edge::Le(a_key, b_key, op) |
+            Edge::Gt(a_key, b_key, op) |
+            Edge::Ge(a_key, b_key, op) |
+            Edge::Prelu(a_key, b_key, op) |
+            Edge::MatMul(a_key, b_key, op) => {
 
                 op(resolve(*a_key)?, resolve(*b_key)?).map_err(|e| ComputationGraphError::from(e))
             },
+            Edge::Conv1d(a_key, kernel_key, bias_key, params, op) => {
+                op(resolve(*a_key)?, resolve(*kernel_key)?, bias_key.map(&resolve).transpose()?, *params).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Conv2d(a_key, kernel_key, bias_key, params, op) => {
+                op(resolve(*a_key)?, resolve(*kernel_key)?, bias_key.map(&resolve).transpose()?, *params).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Conv3d(a_key, kernel_key, bias_key, params, op) => {
+                op(resolve(*a_key)?, resolve(*kernel_key)?, bias_key.map(&resolve).transpose()?, *params).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::ConvTranspose2d(a_key, kernel_key, bias_key, params, output_padding, op) => {
+                op(resolve(*a_key)?, resolve(*kernel_key)?, bias_key.map(&resolve).transpose()?, *params, *output_padding).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::MaxPool2d(a_key, kernel, stride, padding, dilation, op) => {
+                op(resolve(*a_key)?, *kernel, *stride, *padding, *dilation).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::AvgPool2d(a_key, kernel, stride, padding, dilation, count_include_pad, op) => {
+                op(resolve(*a_key)?, *kernel, *stride, *padding, *dilation, *count_include_pad).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::MaxUnpool2d(a_key, indices, output_size, op) => {
+                op(resolve(*a_key)?, indices.as_ref(), *output_size).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::AdaptiveAvgPool2d(a_key, output_size, op) |
+            Edge::AdaptiveMaxPool2d(a_key, output_size, op) => {
+                op(resolve(*a_key)?, *output_size).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::BatchNormNoRunning(a_key, weight_key, bias_key, eps, op) |
+            Edge::InstanceNorm(a_key, weight_key, bias_key, eps, op) => {
+                op(resolve(*a_key)?, resolve(*weight_key)?, resolve(*bias_key)?, *eps).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::BatchNormRunning(a_key, mean_key, var_key, weight_key, bias_key, eps, op) => {
+                op(resolve(*a_key)?, resolve(*mean_key)?, resolve(*var_key)?, resolve(*weight_key)?, resolve(*bias_key)?, *eps).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::LayerNorm(a_key, weight_key, bias_key, normalized_shape, eps, op) => {
+                op(resolve(*a_key)?, normalized_shape, resolve(*weight_key)?, resolve(*bias_key)?, *eps).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::GroupNorm(a_key, weight_key, bias_key, groups, eps, op) => {
+                op(resolve(*a_key)?, *groups, resolve(*weight_key)?, resolve(*bias_key)?, *eps).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::RmsNorm(a_key, weight_key, normalized_shape, eps, op) => {
+                op(resolve(*a_key)?, normalized_shape, resolve(*weight_key)?, *eps).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::BatchNormRunningMean(a_key, running_key, momentum, op) |
+            Edge::BatchNormRunningVar(a_key, running_key, momentum, op) => {
+                op(resolve(*a_key)?, resolve(*running_key)?, *momentum).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Sum(a_key, axes, keep_dims, op) |
+            Edge::Mean(a_key, axes, keep_dims, op) |
+            Edge::Max(a_key, axes, keep_dims, op) |
+            Edge::Min(a_key, axes, keep_dims, op) |
+            Edge::Product(a_key, axes, keep_dims, op) => {
+                op(resolve(*a_key)?, axes, *keep_dims).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Softmax(a_key, axis, op) |
+            Edge::LogSoftmax(a_key, axis, op) => {
+                op(resolve(*a_key)?, *axis).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Concat(keys, axis, op) |
+            Edge::Stack(keys, axis, op) => {
+                let tensors = keys.iter().map(|k| resolve(*k)).collect::<Result<Vec<_>, _>>()?;
+
+                op(&tensors, *axis).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Permute(a_key, dims, op) => {
+                op(resolve(*a_key)?, dims).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Transpose(a_key, dim0, dim1, op) => {
+                op(resolve(*a_key)?, *dim0, *dim1).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Reshape(a_key, shape, op) => {
+                op(resolve(*a_key)?, shape).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Flatten(a_key, start, end, op) => {
+                op(resolve(*a_key)?, *start, *end).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Unflatten(a_key, axis, sizes, op) => {
+                op(resolve(*a_key)?, *axis, sizes).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Narrow(a_key, axis, start, length, op) => {
+                op(resolve(*a_key)?, *axis, *start, *length).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::ScaledDotProductAttention(q_key, k_key, v_key, mask_key, causal_offset, op) => {
+                let mask = mask_key.map(&resolve).transpose()?;
+
+                op(resolve(*q_key)?, resolve(*k_key)?, resolve(*v_key)?, mask, *causal_offset).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Embedding(weight_key, ids, padding_idx, max_norm, op) => {
+                op(resolve(*weight_key)?, ids.as_ref(), *padding_idx, *max_norm).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::EmbeddingBag(weight_key, ids, mode, padding_idx, op) => {
+                op(resolve(*weight_key)?, ids.as_ref(), *mode, *padding_idx).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::IndexSelect(a_key, axis, index, op) |
+            Edge::Gather(a_key, axis, index, op) => {
+                op(resolve(*a_key)?, *axis, index.as_ref()).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Scatter(a_key, axis, index, src_key, op) |
+            Edge::ScatterAdd(a_key, axis, index, src_key, op) => {
+                op(resolve(*a_key)?, *axis, index.as_ref(), resolve(*src_key)?).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::MseLoss(a_key, target_key, reduction, op) |
+            Edge::L1Loss(a_key, target_key, reduction, op) |
+            Edge::BinaryCrossEntropyWithLogits(a_key, target_key, reduction, op) => {
+                op(resolve(*a_key)?, resolve(*target_key)?, *reduction).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::HuberLoss(a_key, target_key, param, reduction, op) |
+            Edge::CrossEntropy(a_key, target_key, param, reduction, op) => {
+                op(resolve(*a_key)?, resolve(*target_key)?, *param, *reduction).map_err(|e| ComputationGraphError::from(e))
+            },
+            Edge::Scan(init_key, capture_keys, scan) => {
+                let captures = capture_keys.iter().map(|k| resolve(*k)).collect::<Result<Vec<_>, _>>()?;
+
+                scan.run(resolve(*init_key)?, &captures)
+            },
+        }
+    }
+
+    //Shape of the output without computing it
+    pub fn infer_shape<F: Fn(NodeKey) -> Result<Shape, ComputationGraphError>>(&self, resolve: F) -> Result<Shape, ComputationGraphError> {
+        match self {
+            Edge::Root => Err(ComputationGraphError::RootNodeNotComputed()),
+            Edge::Abs(a_key, _) |
+            Edge::Neg(a_key, _) |
+            Edge::Relu(a_key, _) |
+            Edge::LeakyRelu(a_key, _, _) |
+            Edge::Sigmoid(a_key, _) |
+            Edge::Prelu(a_key, _, _) |
+            Edge::Silu(a_key, _) |
+            Edge::Gelu(a_key, _) |
+            Edge::GeluTanh(a_key, _) |
+            Edge::Mish(a_key, _) |
+            Edge::Selu(a_key, _) |
+            Edge::Hardswish(a_key, _) |
+            Edge::Elu(a_key, _, _) |
+            Edge::Softplus(a_key, _, _, _) |
+            Edge::Hardtanh(a_key, _, _, _) |
+            Edge::Clamp(a_key, _, _, _) |
+            Edge::Dropout(a_key, _, _, _, _) |
+            Edge::Sqrt(a_key, _) |
+            Edge::Rsqrt(a_key, _) |
+            Edge::Reciprocal(a_key, _) |
+            Edge::Exp(a_key, _) |
+            Edge::Expm1(a_key, _) |
+            Edge::Log(a_key, _) |
+            Edge::Log1p(a_key, _) |
+            Edge::Sin(a_key, _) |
+            Edge::Cos(a_key, _) |
+            Edge::Tan(a_key, _) |
+            Edge::Sinh(a_key, _) |
+            Edge::Cosh(a_key, _) |
+            Edge::Tanh(a_key, _) |
+            Edge::Asin(a_key, _) |
+            Edge::Acos(a_key, _) |
+            Edge::Atan(a_key, _) |
+            Edge::Asinh(a_key, _) |
+            Edge::Acosh(a_key, _) |
+            Edge::Atanh(a_key, _) |
+            Edge::Floor(a_key, _) |
+            Edge::Ceil(a_key, _) |
+            Edge::Round(a_key, _) |
+            Edge::Sign(a_key, _) |
+            Edge::BatchNormNoRunning(a_key, _, _, _, _) |
+            Edge::BatchNormRunning(a_key, _, _, _, _, _, _) |
+            Edge::LayerNorm(a_key, _, _, _, _, _) |
+            Edge::GroupNorm(a_key, _, _, _, _, _) |
+            Edge::InstanceNorm(a_key, _, _, _, _) |
+            Edge::RmsNorm(a_key, _, _, _, _) |
+            Edge::BatchNormRunningMean(_, a_key, _, _) |
+            Edge::BatchNormRunningVar(_, a_key, _, _) |
+            Edge::AddScalar(_, a_key, _) |
+            Edge::SubScalarLH(_, a_key, _) |
+            Edge::SubScalarRH(a_key, _, _) |
+            Edge::MulScalar(_, a_key, _) |
+            Edge::DivScalarLH(_, a_key, _) |
+            Edge::DivScalarRH(a_key, _, _) |
+            Edge::PowScalar(a_key, _, _) => resolve(*a_key),
+            Edge::Add(a_key, b_key, _) |
+            Edge::Sub(a_key, b_key, _) |
+            Edge::Mul(a_key, b_key, _) |
+            Edge::Div(a_key, b_key, _) |
+            Edge::Pow(a_key, b_key, _) |
+            Edge::Maximum(a_key, b_key, _) |
+            Edge::Minimum(a_key, b_key, _) |
+            Edge::Eq(a_key, b_key, _) |
+            Edge::Ne(a_key, b_key, _) |
+            Edge::Lt(a_key, b_key, _) |
+            Edge::Le(a_key, b_key, _) |
+            Edge::Gt(a_key, b_key, _) |
+            Edge::Ge(a_key, b_key, _) => {
+                let a_shape = resolve(*a_key)?;
+                let b_shape = resolve(*b_key)?;
+
+                match a_shape.broadcast_with(&b_shape) {
+                    Some(shape) => Ok(shape),
+                    None => Err(EngineError::ShapeMismatch(a_shape, b_shape).into()),
+                }
+            },
+            Edge::Where(cond_key, a_key, b_key, _) => {
+                let cond_shape = resolve(*cond_key)?;
+                let a_shape = resolve(*a_key)?;
+                let b_shape = resolve(*b_key)?;
+
+                match cond_shape.broadcast_with(&a_shape).and_then(|s| s.broadcast_with(&b_shape)) {
+                    Some(shape) => Ok(shape),
+                    None => Err(EngineError::ShapeMismatch(cond_shape, a_shape).into()),
+                }
+            },
+            Edge::MaskedFill(a_key, mask_key, _, _) => {
+                let a_shape = resolve(*a_key)?;
+                let mask_shape = resolve(*mask_key)?;
+
+                match a_shape.broadcast_with(&mask_shape) {
+                    Some(shape) if shape == a_shape => Ok(a_shape),
+                    _ => Err(EngineError::ShapeMismatch(mask_shape, a_shape).into()),
+                }
+            },
+            Edge::MatMul(a_key, b_key, _) => Ok(matmul_shape(&resolve(*a_key)?, &resolve(*b_key)?)?),
+            Edge::Conv1d(a_key, kernel_key, _, params, _) => Ok(conv_shape(&resolve(*a_key)?, &resolve(*kernel_key)?, params)?),
+            Edge::Conv2d(a_key, kernel_key, _, params, _) => Ok(conv_shape(&resolve(*a_key)?, &resolve(*kernel_key)?, params)?),
+            Edge::Conv3d(a_key, kernel_key, _, params, _) => Ok(conv_shape(&resolve(*a_key)?, &resolve(*kernel_key)?, params)?),
+            Edge::ConvTranspose2d(a_key, kernel_key, _, params, output_padding, _) => Ok(conv_transpose_shape(&resolve(*a_key)?, &resolve(*kernel_key)?, params, output_padding)?),
+            Edge::MaxPool2d(a_key, kernel, stride, padding, dilation, _) |
+            Edge::AvgPool2d(a_key, kernel, stride, padding, dilation, _, _) => Ok(pool_2d_shape(&resolve(*a_key)?, *kernel, *stride, *padding, *dilation)?),
+            Edge::MaxUnpool2d(a_key, _, output_size, _) |
+            Edge::AdaptiveAvgPool2d(a_key, output_size, _) |
+            Edge::AdaptiveMaxPool2d(a_key, output_size, _) => Ok(adaptive_pool_2d_shape(&resolve(*a_key)?, *output_size)?),
+            Edge::Sum(a_key, axes, keep_dims, _) |
+            Edge::Mean(a_key, axes, keep_dims, _) |
+            Edge::Max(a_key, axes, keep_dims, _) |
+            Edge::Min(a_key, axes, keep_dims, _) |
+            Edge::Product(a_key, axes, keep_dims, _) => Ok(reduced_axes_shape(&resolve(*a_key)?, axes, *keep_dims)?),
+            Edge::Softmax(a_key, axis, _) |
+            Edge::LogSoftmax(a_key, axis, _) => {
+                let a_shape = resolve(*a_key)?;
+                err_if_too_few_dimensions(&a_shape, axis + 1)?;
+
+                Ok(a_shape)
+            },
+            Edge::Concat(keys, axis, _) => {
+                let shapes = keys.iter().map(|k| resolve(*k)).collect::<Result<Vec<_>, _>>()?;
+
+                Ok(concat_shape(&shapes.iter().collect::<Vec<_>>(), *axis)?)
+            },
+            Edge::Stack(keys, axis, _) => {
+                let shapes = keys.iter().map(|k| resolve(*k)).collect::<Result<Vec<_>, _>>()?;
+
+                Ok(stack_shape(&shapes.iter().collect::<Vec<_>>(), *axis)?)
+            },
+            Edge::Permute(a_key, dims, _) => Ok(permute_shape(&resolve(*a_key)?, dims)?),
+            Edge::Reshape(a_key, shape, _) => Ok(reshape_shape(&resolve(*a_key)?, shape)?),
+            Edge::Flatten(a_key, start, end, _) => Ok(flatten_shape(&resolve(*a_key)?, *start, *end)?),
+            Edge::Unflatten(a_key, axis, sizes, _) => Ok(unflatten_shape(&resolve(*a_key)?, *axis, sizes)?),
+            Edge::Narrow(a_key, axis, start, length, _) => Ok(narrow_shape(&resolve(*a_key)?, *axis, *start, *length)?),
+            Edge::ScaledDotProductAttention(q_key, k_key, v_key, _, _, _) => Ok(attention_shape(&resolve(*q_key)?, &resolve(*k_key)?, &resolve(*v_key)?)?),
+            Edge::Transpose(a_key, dim0, dim1, _) => {
+                let a_shape = resolve(*a_key)?;
+                let dims = transpose_dims(&a_shape, *dim0, *dim1)?;
+
+                Ok(permute_shape(&a_shape, &dims)?)
+            },
+            Edge::Embedding(weight_key, ids, _, _, _) => Ok(embedding_shape(&resolve(*weight_key)?, ids.shape())?),
+            Edge::EmbeddingBag(weight_key, ids, _, _, _) => Ok(embedding_bag_shape(&resolve(*weight_key)?, ids.shape())?),
+            Edge::IndexSelect(a_key, axis, index, _) => Ok(index_select_shape(&resolve(*a_key)?, *axis, index.shape())?),
+            Edge::Gather(a_key, axis, index, _) => {
+                err_if_invalid_index_shape(&resolve(*a_key)?, *axis, index.shape())?;
+
+                Ok(index.shape().clone())
+            },
+            Edge::Scatter(a_key, axis, index, _, _) |
+            Edge::ScatterAdd(a_key, axis, index, _, _) => {
+                let a_shape = resolve(*a_key)?;
+                err_if_invalid_index_shape(&a_shape, *axis, index.shape())?;
+
+                Ok(a_shape)
+            },
+            Edge::MseLoss(a_key, target_key, reduction, _) |
+            Edge::L1Loss(a_key, target_key, reduction, _) |
+            Edge::BinaryCrossEntropyWithLogits(a_key, target_key, reduction, _) |
+            Edge::HuberLoss(a_key, target_key, _, reduction, _) => {
+                let a_shape = resolve(*a_key)?;
+                let target_shape = resolve(*target_key)?;
+
+                if a_shape != target_shape {
+                    return Err(EngineError::ShapeMismatch(a_shape, target_shape).into());
+                }
+
+                Ok(reduced_shape(a_shape, *reduction))
+            },
+            Edge::CrossEntropy(a_key, target_key, _, reduction, _) => {
+                let a_shape = resolve(*a_key)?;
+                let target_shape = resolve(*target_key)?;
+
+                err_if_too_few_dimensions(&a_shape, 2)?;
+
+                let expected_target_shape = Shape::from_iter(a_shape.iter().enumerate().filter_map(|(i, d)| if i == 1 { None } else { Some(d) }));
+
+                if target_shape != expected_target_shape {
+                    return Err(EngineError::ShapeMismatch(target_shape, expected_target_shape).into());
+                }
+
+                Ok(reduced_shape(target_shape, *reduction))
+            },
+            //The carry keeps its shape between iterations
+            Edge::Scan(init_key, _, _) => resolve(*init_key),
         }
     }
 }
 
+fn reduced_shape(shape: Shape, reduction: Reduction) -> Shape {
+    match reduction {
+        Reduction::None => shape,
+        Reduction::Mean | Reduction::Sum => shape![1],
+    }
+}
+
+fn matmul_shape(a_shape: &Shape, b_shape: &Shape) -> Result<Shape, EngineError> {
+    err_if_too_few_dimensions(a_shape, 2)?;
+    err_if_too_few_dimensions(b_shape, 2)?;
+    err_if_dimension_mismatch(a_shape.get(a_shape.len() - 1)?, b_shape.get(b_shape.len() - 2)?)?;
+
+    //The batch dimensions of the tensor with fewer dimensions are broadcast
+    let batches = if a_shape.len() >= b_shape.len() { a_shape } else { b_shape };
+
+    Ok(Shape::from_iter(batches.iter().take(batches.len() - 2).chain([a_shape.get(a_shape.len() - 2)?, b_shape.get(b_shape.len() - 1)?])))
+}
+
 pub struct EdgeNodesIterator<'a, T: UnitCompatible> {
     edge: &'a Edge<T>,
     pos: usize,
@@ -85,12 +620,72 @@ impl<'a, T: UnitCompatible> Iterator for EdgeNodesIterator<'a, T> {
             Edge::Root => None,
             Edge::Abs(a_key, _) | 
             Edge::Neg(a_key, _) |
+            Edge::Relu(a_key, _) |
+            Edge::LeakyRelu(a_key, _, _) |
+            Edge::Sigmoid(a_key, _) |
+            Edge::Silu(a_key, _) |
+            Edge::Gelu(a_key, _) |
+            Edge::GeluTanh(a_key, _) |
+            Edge::Mish(a_key, _) |
+            Edge::Selu(a_key, _) |
+            Edge::Hardswish(a_key, _) |
+            Edge::Elu(a_key, _, _) |
+            Edge::Softplus(a_key, _, _, _) |
+            Edge::Hardtanh(a_key, _, _, _) |
+            Edge::Clamp(a_key, _, _, _) |
+            Edge::Dropout(a_key, _, _, _, _) |
+            Edge::Sqrt(a_key, _) |
+            Edge::Rsqrt(a_key, _) |
+            Edge::Reciprocal(a_key, _) |
+            Edge::Exp(a_key, _) |
+            Edge::Expm1(a_key, _) |
+            Edge::Log(a_key, _) |
+            Edge::Log1p(a_key, _) |
+            Edge::Sin(a_key, _) |
+            Edge::Cos(a_key, _) |
+            Edge::Tan(a_key, _) |
+            Edge::Sinh(a_key, _) |
+            Edge::Cosh(a_key, _) |
+            Edge::Tanh(a_key, _) |
+            Edge::Asin(a_key, _) |
+            Edge::Acos(a_key, _) |
+            Edge::Atan(a_key, _) |
+            Edge::Asinh(a_key, _) |
+            Edge::Acosh(a_key, _) |
+            Edge::Atanh(a_key, _) |
+            Edge::Floor(a_key, _) |
+            Edge::Ceil(a_key, _) |
+            Edge::Round(a_key, _) |
+            Edge::Sign(a_key, _) |
             Edge::AddScalar(_, a_key, _) |
             Edge::SubScalarLH(_, a_key, _) |
             Edge::SubScalarRH(a_key, _, _) |
             Edge::MulScalar(_, a_key, _) |
             Edge::DivScalarLH(_, a_key, _) |
-            Edge::DivScalarRH(a_key, _, _) => {
+            Edge::DivScalarRH(a_key, _, _) |
+            Edge::PowScalar(a_key, _, _) |
+            Edge::MaxPool2d(a_key, _, _, _, _, _) |
+            Edge::MaxUnpool2d(a_key, _, _, _) |
+            Edge::AvgPool2d(a_key, _, _, _, _, _, _) |
+            Edge::AdaptiveAvgPool2d(a_key, _, _) |
+            Edge::AdaptiveMaxPool2d(a_key, _, _) |
+            Edge::Sum(a_key, _, _, _) |
+            Edge::Mean(a_key, _, _, _) |
+            Edge::Max(a_key, _, _, _) |
+            Edge::Min(a_key, _, _, _) |
+            Edge::Product(a_key, _, _, _) |
+            Edge::Softmax(a_key, _, _) |
+            Edge::Permute(a_key, _, _) |
+            Edge::IndexSelect(a_key, _, _, _) |
+            Edge::Embedding(a_key, _, _, _, _) |
+            Edge::EmbeddingBag(a_key, _, _, _, _) |
+            Edge::Gather(a_key, _, _, _) |
+            Edge::Transpose(a_key, _, _, _) |
+            Edge::Reshape(a_key, _, _) |
+            Edge::Flatten(a_key, _, _, _) |
+            Edge::Unflatten(a_key, _, _, _) |
+            Edge::Narrow(a_key, _, _, _, _) |
+            Edge::LogSoftmax(a_key, _, _) => {
                 match self.pos {
                     0 => Some(*a_key),
                     _ => None,
@@ -99,13 +694,83 @@ impl<'a, T: UnitCompatible> Iterator for EdgeNodesIterator<'a, T> {
             Edge::Add(a_key, b_key, _) |
             Edge::Sub(a_key, b_key, _) |
             Edge::Mul(a_key, b_key, _) |
-            Edge::Div(a_key, b_key, _) => {
+            Edge::Div(a_key, b_key, _) |
+            Edge::Pow(a_key, b_key, _) |
+            Edge::Maximum(a_key, b_key, _) |
+            Edge::Minimum(a_key, b_key, _) |
+            Edge::Eq(a_key, b_key, _) |
+            Edge::Ne(a_key, b_key, _) |
+            Edge::Lt(a_key, b_key, _) |
+            Edge::Le(a_key, b_key, _) |
+            Edge::Gt(a_key, b_key, _) |
+            Edge::Ge(a_key, b_key, _) |
+            Edge::MaskedFill(a_key, b_key, _, _) |
+            Edge::Scatter(a_key, _, _, b_key, _) |
+            Edge::ScatterAdd(a_key, _, _, b_key, _) |
+            Edge::Prelu(a_key, b_key, _) |
+            Edge::MatMul(a_key, b_key, _) |
+            Edge::MseLoss(a_key, b_key, _, _) |
+            Edge::L1Loss(a_key, b_key, _, _) |
+            Edge::BinaryCrossEntropyWithLogits(a_key, b_key, _, _) |
+            Edge::HuberLoss(a_key, b_key, _, _, _) |
+            Edge::CrossEntropy(a_key, b_key, _, _, _) |
+            Edge::BatchNormRunningMean(a_key, b_key, _, _) |
+            Edge::BatchNormRunningVar(a_key, b_key, _, _) |
+            Edge::RmsNorm(a_key, b_key, _, _, _) |
+            Edge::Conv1d(a_key, b_key, None, _, _) |
+            Edge::Conv2d(a_key, b_key, None, _, _) |
+            Edge::Conv3d(a_key, b_key, None, _, _) |
+            Edge::ConvTranspose2d(a_key, b_key, None, _, _, _) => {
                 match self.pos {
                     0 => Some(*a_key),
                     1 => Some(*b_key),
                     _ => None,
                 }
             }
+            Edge::Conv1d(a_key, b_key, Some(c_key), _, _) |
+            Edge::Conv2d(a_key, b_key, Some(c_key), _, _) |
+            Edge::Conv3d(a_key, b_key, Some(c_key), _, _) |
+            Edge::ConvTranspose2d(a_key, b_key, Some(c_key), _, _, _) |
+            Edge::BatchNormNoRunning(a_key, b_key, c_key, _, _) |
+            Edge::LayerNorm(a_key, b_key, c_key, _, _, _) |
+            Edge::GroupNorm(a_key, b_key, c_key, _, _, _) |
+            Edge::InstanceNorm(a_key, b_key, c_key, _, _) |
+            Edge::ScaledDotProductAttention(a_key, b_key, c_key, None, _, _) |
+            Edge::Where(a_key, b_key, c_key, _) => {
+                match self.pos {
+                    0 => Some(*a_key),
+                    1 => Some(*b_key),
+                    2 => Some(*c_key),
+                    _ => None,
+                }
+            }
+            Edge::ScaledDotProductAttention(a_key, b_key, c_key, Some(d_key), _, _) => {
+                match self.pos {
+                    0 => Some(*a_key),
+                    1 => Some(*b_key),
+                    2 => Some(*c_key),
+                    3 => Some(*d_key),
+                    _ => None,
+                }
+            }
+            Edge::BatchNormRunning(a_key, b_key, c_key, d_key, e_key, _, _) => {
+                match self.pos {
+                    0 => Some(*a_key),
+                    1 => Some(*b_key),
+                    2 => Some(*c_key),
+                    3 => Some(*d_key),
+                    4 => Some(*e_key),
+                    _ => None,
+                }
+            }
+            Edge::Concat(keys, _, _) |
+            Edge::Stack(keys, _, _) => keys.get(self.pos).copied(),
+            Edge::Scan(init_key, capture_keys, _) => {
+                match self.pos {
+                    0 => Some(*init_key),
+                    pos => capture_keys.get(pos - 1).copied(),
+                }
+            }
         };
 
         if out.is_some() {
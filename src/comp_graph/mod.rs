@@ -1,32 +1,46 @@
 mod edge;
+mod plan;
+mod scan;
 
-use std::collections::{HashSet, HashMap};
+use std::{cell::RefCell, collections::{HashSet, HashMap}, rc::Rc};
 
+use itertools::Itertools;
 use slotmap::{SlotMap, new_key_type};
 use thiserror::Error;
 
-use crate::engine::{tensor::{factory::EngineTensorFactory, unit_iter::EngineTensorUnitIterator, EngineTensor}, unit::UnitCompatible, Engine, EngineError};
+use crate::{engine::{tensor::{factory::EngineTensorFactory, unit_iter::EngineTensorUnitIterator, EngineTensor}, unit::UnitCompatible, ConvParams, EmbeddingBagMode, Engine, EngineError, Reduction}, helper::{Rng, Shape}};
 
-use self::edge::Edge;
+use self::{edge::Edge, scan::Scan};
+
+pub use self::plan::ExecutionPlan;
 
 #[derive(Debug)]
 pub struct Node<T: UnitCompatible> {
     tensor: Option<Box<dyn EngineTensor<Unit = T>>>,
-    edge: Edge<T>,
+    //Shared with the plans that compute this node
+    edge: Rc<Edge<T>>,
 }
 
 impl<T: UnitCompatible> Node<T> {
     fn create_root(tensor: Box<dyn EngineTensor<Unit = T>>) -> Self {
         Self {
             tensor: Some(tensor),
-            edge: Edge::Root,
+            edge: Rc::new(Edge::Root),
         }
     }
 
     fn create_node(edge: Edge<T>) -> Self {
         Self {
             tensor: None,
-            edge,
+            edge: Rc::new(edge),
+        }
+    }
+
+    //Placeholders are roots without a tensor that have one bound during evaluation
+    fn create_placeholder() -> Self {
+        Self {
+            tensor: None,
+            edge: Rc::new(Edge::Root),
         }
     }
 
@@ -51,8 +65,12 @@ impl<T: UnitCompatible> Node<T> {
         &self.edge
     }
 
+    fn shared_edge(&self) -> Rc<Edge<T>> {
+        self.edge.clone()
+    }
+
     fn is_root(&self) -> bool {
-        *self.edge() == Edge::Root
+        self.edge().is_root()
     }
 }
 
@@ -101,10 +119,18 @@ impl<T: UnitCompatible> CompGraph<T> {
         self.nodes.insert(Node::create_node(edge))
     }
 
+    fn create_placeholder(&mut self) -> CompGraphTensor {
+        CompGraphTensor::new(self.nodes.insert(Node::create_placeholder()))
+    }
+
     pub fn iter(&self, tensor: &CompGraphTensor) -> EngineTensorUnitIterator<T> {
         EngineTensorUnitIterator::new(self.get_node(tensor.node_key()).unwrap().tensor().unwrap())
     }
 
+    pub fn tensor(&self, tensor: &CompGraphTensor) -> Result<&dyn EngineTensor<Unit = T>, ComputationGraphError> {
+        self.get_node_error(tensor.node_key())?.tensor().ok_or(ComputationGraphError::NodeNotComputed(*tensor.node_key()))
+    }
+
     //First return is open nodes, second is node_to_children
     //The algorithm is more efficient if done at the same time
     fn generate_node_to_children(&self, target: &NodeKey) -> Result<(Vec<NodeKey>, HashMap::<NodeKey, Vec<NodeKey>>), ComputationGraphError> {
@@ -194,39 +220,30 @@ impl<T: UnitCompatible> CompGraph<T> {
     }
 
     fn non_populating_eval_node(&mut self, target: NodeKey) -> Result<(), ComputationGraphError> {
-        //Nodes that have all dependencies satisfied
+        let plan = self.compile_node(target)?;
+
+        self.run(&plan)
+    }
+
+    pub fn non_populating_eval(&mut self, target: &CompGraphTensor) -> Result<(), ComputationGraphError> {
+        self.non_populating_eval_node(*target.node_key())
+    }
+
+    //Kahn's Algorithm without computing anything
+    //Only non root nodes are returned since roots don't need computing
+    fn topological_order(&self, target: NodeKey) -> Result<Vec<NodeKey>, ComputationGraphError> {
         let (open_roots, node_to_children) = self.generate_node_to_children(&target)?;
 
-        //Current open set of nodes
         let mut open = open_roots.clone();
-
-        //Nodes already processed (in order to find more open nodes)
         let mut processed_nodes = HashSet::<NodeKey>::from_iter(open.clone());
 
-        //Cache for calculated nodes
-        //Should be cleared once no dependencies left
-        let mut comp_cache = HashMap::<NodeKey, Box<dyn EngineTensor<Unit = T>>>::new();
+        let mut order = Vec::<NodeKey>::new();
 
         while let Some(node_key) = open.pop() {
             let node = self.get_node(&node_key).ok_or(ComputationGraphError::NodeDoesNotExist(target))?;
 
             if !node.is_root() {
-                let comp_tensor = node.edge().compute_tensor(
-                    |k| {
-                        match comp_cache.get(&k) {
-                            Some(tensor) => Ok(tensor.as_ref()),
-                            None => Ok(self.get_node(&k).ok_or(ComputationGraphError::NodeDoesNotExist(k))?.tensor().ok_or(ComputationGraphError::NodeNotComputed(k))?),
-                        }
-                    }
-                )?;
-                comp_cache.insert(node_key, comp_tensor);
-
-                //All children are defined in the cache so the parent is no longer needed
-                for parent_key in node.edge().nodes() {
-                    if node_to_children.get(&parent_key).unwrap().iter().all(|k| comp_cache.contains_key(k)) {
-                        comp_cache.remove(&parent_key);
-                    }
-                }
+                order.push(node_key);
             }
 
             processed_nodes.insert(node_key);
@@ -237,23 +254,185 @@ impl<T: UnitCompatible> CompGraph<T> {
 
                     if child_node.edge().is_root() {
                         return Err(ComputationGraphError::RootNodeIsChild(*child_key));
-                    } else {
-                        //If all parents of the child are processed and the child hasn't been processed before then add it to be processed
-                        if child_node.edge().nodes().all(|k| processed_nodes.contains(&k)) && !processed_nodes.contains(child_key) {
-                            open.push(*child_key);
-                        }
+                    } else if child_node.edge().nodes().all(|k| processed_nodes.contains(&k)) && !processed_nodes.contains(child_key) {
+                        open.push(*child_key);
                     }
                 }
             }
         }
 
-        self.get_node_mut(&target).ok_or(ComputationGraphError::NodeDoesNotExist(target))?.set_tensor(comp_cache.remove(&target).ok_or(ComputationGraphError::NodeDoesNotExist(target))?);
+        Ok(order)
+    }
+
+    fn compile_node(&self, target: NodeKey) -> Result<ExecutionPlan<T>, ComputationGraphError> {
+        let order = self.topological_order(target)?;
+
+        ExecutionPlan::new(target, &order, |k| Ok(self.get_node_error(&k)?.shared_edge()))
+    }
+
+    //Nodes are never modified once created so a plan stays valid for the lifetime of the graph
+    //The plan holds the ops of the graph it was compiled from and should only be run on that graph
+    pub fn compile(&self, target: &CompGraphTensor) -> Result<ExecutionPlan<T>, ComputationGraphError> {
+        self.compile_node(*target.node_key())
+    }
+
+    //Shapes of every node needed for target (in topological order) without computing anything
+    fn infer_shapes(&self, order: &[NodeKey]) -> Result<HashMap<NodeKey, Shape>, ComputationGraphError> {
+        let mut shapes = HashMap::<NodeKey, Shape>::new();
+
+        for node_key in order {
+            let shape = self.get_node_error(node_key)?.edge().infer_shape(|k| {
+                match shapes.get(&k) {
+                    Some(shape) => Ok(shape.clone()),
+                    None => Ok(self.get_node_error(&k)?.tensor().ok_or(ComputationGraphError::NodeNotComputed(k))?.shape().clone()),
+                }
+            })?;
+
+            shapes.insert(*node_key, shape);
+        }
+
+        Ok(shapes)
+    }
+
+    //Greedy list scheduling that always picks the open node which grows live memory the least
+    //Finding the optimal order is NP-hard but this handles the common case of independent branches well
+    fn memory_planned_order(&self, target: NodeKey, node_bytes: &HashMap<NodeKey, usize>) -> Result<Vec<NodeKey>, ComputationGraphError> {
+        let (open_roots, node_to_children) = self.generate_node_to_children(&target)?;
+
+        let mut processed_nodes = HashSet::<NodeKey>::from_iter(open_roots.iter().copied());
+
+        //Children not yet scheduled for each node
+        let mut remaining_children = HashMap::<NodeKey, usize>::from_iter(node_to_children.iter().map(|(k, c)| (*k, c.len())));
+
+        let unique_parents = |node_key: NodeKey| -> Result<Vec<NodeKey>, ComputationGraphError> {
+            Ok(self.get_node_error(&node_key)?.edge().nodes().unique().collect())
+        };
+
+        let mut ready = Vec::<NodeKey>::new();
+
+        for root_key in open_roots.iter() {
+            for child_key in node_to_children.get(root_key).into_iter().flatten() {
+                if !ready.contains(child_key) && unique_parents(*child_key)?.iter().all(|k| processed_nodes.contains(k)) {
+                    ready.push(*child_key);
+                }
+            }
+        }
+
+        let mut order = Vec::<NodeKey>::new();
+
+        while !ready.is_empty() {
+            let mut best: Option<(usize, i64)> = None;
+
+            for (ready_index, node_key) in ready.iter().enumerate() {
+                let freed = unique_parents(*node_key)?.iter()
+                    .filter(|k| **k != target && remaining_children.get(k) == Some(&1))
+                    .filter_map(|k| node_bytes.get(k))
+                    .sum::<usize>();
+
+                let delta = *node_bytes.get(node_key).unwrap() as i64 - freed as i64;
+
+                if best.is_none_or(|(_, best_delta)| delta < best_delta) {
+                    best = Some((ready_index, delta));
+                }
+            }
+
+            let node_key = ready.remove(best.unwrap().0);
+
+            order.push(node_key);
+            processed_nodes.insert(node_key);
+
+            for parent_key in unique_parents(node_key)? {
+                if let Some(remaining) = remaining_children.get_mut(&parent_key) {
+                    *remaining -= 1;
+                }
+            }
+
+            for child_key in node_to_children.get(&node_key).into_iter().flatten() {
+                if !processed_nodes.contains(child_key) && !ready.contains(child_key) && unique_parents(*child_key)?.iter().all(|k| processed_nodes.contains(k)) {
+                    ready.push(*child_key);
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    //Like compile but orders nodes to reduce the peak memory of intermediate tensors
+    //Roots are held by the graph regardless so they aren't counted
+    //Fails before anything is computed if the predicted peak exceeds budget (in bytes)
+    pub fn compile_memory_planned(&self, target: &CompGraphTensor, budget: Option<usize>) -> Result<ExecutionPlan<T>, ComputationGraphError> {
+        let target = *target.node_key();
+
+        let shapes = self.infer_shapes(&self.topological_order(target)?)?;
+        let node_bytes = HashMap::<NodeKey, usize>::from_iter(shapes.iter().map(|(k, shape)| (*k, shape.elements() * std::mem::size_of::<T>())));
+
+        let order = self.memory_planned_order(target, &node_bytes)?;
+
+        let plan = ExecutionPlan::new(target, &order, |k| Ok(self.get_node_error(&k)?.shared_edge()))?
+            .with_predicted_peak(|k| *node_bytes.get(&k).unwrap());
+
+        match (plan.predicted_peak_bytes(), budget) {
+            (Some(peak), Some(budget)) if peak > budget => Err(ComputationGraphError::MemoryBudgetExceeded(peak, budget)),
+            _ => Ok(plan),
+        }
+    }
+
+    //Runs a plan against the current root tensors and stores the result in the target node
+    pub fn run(&mut self, plan: &ExecutionPlan<T>) -> Result<(), ComputationGraphError> {
+        let comp_tensor = self.run_bound(plan, &HashMap::new())?;
+
+        self.get_node_mut(&plan.target()).ok_or(ComputationGraphError::NodeDoesNotExist(plan.target()))?.set_tensor(comp_tensor);
 
         Ok(())
     }
 
-    pub fn non_populating_eval(&mut self, target: &CompGraphTensor) -> Result<(), ComputationGraphError> {
-        self.non_populating_eval_node(*target.node_key())
+    //Bound tensors take precedence over the tensor stored in a node (used to fill placeholders)
+    fn run_bound(&self, plan: &ExecutionPlan<T>, bound: &HashMap<NodeKey, &dyn EngineTensor<Unit = T>>) -> Result<Box<dyn EngineTensor<Unit = T>>, ComputationGraphError> {
+        let resolve_stored = |k: NodeKey| -> Result<&dyn EngineTensor<Unit = T>, ComputationGraphError> {
+            match bound.get(&k) {
+                Some(tensor) => Ok(*tensor),
+                None => self.get_node(&k).ok_or(ComputationGraphError::NodeDoesNotExist(k))?.tensor().ok_or(ComputationGraphError::NodeNotComputed(k)),
+            }
+        };
+
+        //Nothing to compute so the stored tensor is the result
+        if plan.steps().is_empty() {
+            return Ok(resolve_stored(plan.target())?.clone());
+        }
+
+        let mut buffers = Vec::<Option<Box<dyn EngineTensor<Unit = T>>>>::from_iter(plan.steps().iter().map(|_| None));
+
+        for step in plan.steps() {
+            let comp_tensor = step.edge().compute_tensor(
+                |k| {
+                    match step.operand_slot(k) {
+                        Some(slot) => buffers.get(slot).and_then(|b| b.as_deref()).ok_or(ComputationGraphError::NodeNotComputed(k)),
+                        None => resolve_stored(k),
+                    }
+                }
+            )?;
+
+            *buffers.get_mut(step.slot()).unwrap() = Some(comp_tensor);
+
+            for slot in step.frees() {
+                *buffers.get_mut(*slot).unwrap() = None;
+            }
+        }
+
+        plan.slot(plan.target()).and_then(|slot| buffers.get_mut(slot)).and_then(|b| b.take()).ok_or(ComputationGraphError::NodeNotComputed(plan.target()))
+    }
+
+    //Swaps the tensor of a root so plans can be rerun on new inputs
+    pub fn replace_root(&mut self, root: &CompGraphTensor, tensor: Box<dyn EngineTensor<Unit = T>>) -> Result<(), ComputationGraphError> {
+        let node = self.get_node_mut_error(root.node_key())?;
+
+        if node.is_root() {
+            node.set_tensor(tensor);
+
+            Ok(())
+        } else {
+            Err(ComputationGraphError::CannotReplaceNonRoot(*root.node_key()))
+        }
     }
 
     pub fn abs<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
@@ -264,6 +443,178 @@ impl<T: UnitCompatible> CompGraph<T> {
         CompGraphTensor::new(self.create_node(Edge::Neg(*a.node_key(), E::neg::<F>)))
     }
 
+    pub fn relu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Relu(*a.node_key(), E::relu::<F>)))
+    }
+
+    pub fn leaky_relu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, alpha: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::LeakyRelu(*a.node_key(), alpha, E::leaky_relu::<F>)))
+    }
+
+    pub fn sigmoid<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sigmoid(*a.node_key(), E::sigmoid::<F>)))
+    }
+
+    pub fn silu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Silu(*a.node_key(), E::silu::<F>)))
+    }
+
+    pub fn gelu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Gelu(*a.node_key(), E::gelu::<F>)))
+    }
+
+    pub fn gelu_tanh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::GeluTanh(*a.node_key(), E::gelu_tanh::<F>)))
+    }
+
+    pub fn mish<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Mish(*a.node_key(), E::mish::<F>)))
+    }
+
+    pub fn selu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Selu(*a.node_key(), E::selu::<F>)))
+    }
+
+    pub fn hardswish<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Hardswish(*a.node_key(), E::hardswish::<F>)))
+    }
+
+    pub fn elu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, alpha: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Elu(*a.node_key(), alpha, E::elu::<F>)))
+    }
+
+    pub fn softplus<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, beta: f64, threshold: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Softplus(*a.node_key(), beta, threshold, E::softplus::<F>)))
+    }
+
+    pub fn hardtanh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, min_val: f64, max_val: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Hardtanh(*a.node_key(), min_val, max_val, E::hardtanh::<F>)))
+    }
+
+    pub fn prelu<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, weight: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Prelu(*a.node_key(), *weight.node_key(), E::prelu::<F>)))
+    }
+
+    pub fn sqrt<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sqrt(*a.node_key(), E::sqrt::<F>)))
+    }
+
+    pub fn rsqrt<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Rsqrt(*a.node_key(), E::rsqrt::<F>)))
+    }
+
+    pub fn reciprocal<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Reciprocal(*a.node_key(), E::reciprocal::<F>)))
+    }
+
+    pub fn exp<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Exp(*a.node_key(), E::exp::<F>)))
+    }
+
+    pub fn expm1<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Expm1(*a.node_key(), E::expm1::<F>)))
+    }
+
+    pub fn log<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Log(*a.node_key(), E::log::<F>)))
+    }
+
+    pub fn log1p<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Log1p(*a.node_key(), E::log1p::<F>)))
+    }
+
+    pub fn sin<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sin(*a.node_key(), E::sin::<F>)))
+    }
+
+    pub fn cos<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Cos(*a.node_key(), E::cos::<F>)))
+    }
+
+    pub fn tan<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Tan(*a.node_key(), E::tan::<F>)))
+    }
+
+    pub fn sinh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sinh(*a.node_key(), E::sinh::<F>)))
+    }
+
+    pub fn cosh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Cosh(*a.node_key(), E::cosh::<F>)))
+    }
+
+    pub fn tanh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Tanh(*a.node_key(), E::tanh::<F>)))
+    }
+
+    pub fn asin<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Asin(*a.node_key(), E::asin::<F>)))
+    }
+
+    pub fn acos<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Acos(*a.node_key(), E::acos::<F>)))
+    }
+
+    pub fn atan<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Atan(*a.node_key(), E::atan::<F>)))
+    }
+
+    pub fn asinh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Asinh(*a.node_key(), E::asinh::<F>)))
+    }
+
+    pub fn acosh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Acosh(*a.node_key(), E::acosh::<F>)))
+    }
+
+    pub fn atanh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Atanh(*a.node_key(), E::atanh::<F>)))
+    }
+
+    pub fn floor<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Floor(*a.node_key(), E::floor::<F>)))
+    }
+
+    pub fn ceil<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Ceil(*a.node_key(), E::ceil::<F>)))
+    }
+
+    pub fn round<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Round(*a.node_key(), E::round::<F>)))
+    }
+
+    pub fn sign<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sign(*a.node_key(), E::sign::<F>)))
+    }
+
+    pub fn add_scalar<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, s: T, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::AddScalar(s, *a.node_key(), E::add_scalar::<F>)))
+    }
+
+    pub fn sub_scalar_lh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, s: T, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::SubScalarLH(s, *a.node_key(), E::sub_scalar_lh::<F>)))
+    }
+
+    pub fn sub_scalar_rh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, s: T) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::SubScalarRH(*a.node_key(), s, E::sub_scalar_rh::<F>)))
+    }
+
+    pub fn mul_scalar<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, s: T, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MulScalar(s, *a.node_key(), E::mul_scalar::<F>)))
+    }
+
+    pub fn div_scalar_lh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, s: T, a: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::DivScalarLH(s, *a.node_key(), E::div_scalar_lh::<F>)))
+    }
+
+    pub fn div_scalar_rh<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, s: T) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::DivScalarRH(*a.node_key(), s, E::div_scalar_rh::<F>)))
+    }
+
+    pub fn pow_scalar<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, s: T) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::PowScalar(*a.node_key(), s, E::pow_scalar::<F>)))
+    }
+
     pub fn add<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
         CompGraphTensor::new(self.create_node(Edge::Add(*a.node_key(), *b.node_key(), E::add::<F>)))
     }
@@ -279,6 +630,276 @@ impl<T: UnitCompatible> CompGraph<T> {
     pub fn div<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
         CompGraphTensor::new(self.create_node(Edge::Div(*a.node_key(), *b.node_key(), E::div::<F>)))
     }
+
+    pub fn pow<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Pow(*a.node_key(), *b.node_key(), E::pow::<F>)))
+    }
+
+    pub fn maximum<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Maximum(*a.node_key(), *b.node_key(), E::maximum::<F>)))
+    }
+
+    pub fn minimum<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Minimum(*a.node_key(), *b.node_key(), E::minimum::<F>)))
+    }
+
+    pub fn eq<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Eq(*a.node_key(), *b.node_key(), E::eq::<F>)))
+    }
+
+    pub fn ne<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Ne(*a.node_key(), *b.node_key(), E::ne::<F>)))
+    }
+
+    pub fn lt<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Lt(*a.node_key(), *b.node_key(), E::lt::<F>)))
+    }
+
+    pub fn le<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Le(*a.node_key(), *b.node_key(), E::le::<F>)))
+    }
+
+    pub fn gt<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Gt(*a.node_key(), *b.node_key(), E::gt::<F>)))
+    }
+
+    pub fn ge<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Ge(*a.node_key(), *b.node_key(), E::ge::<F>)))
+    }
+
+    //Draws the seed of the node's own rng from rng, see Edge::Dropout
+    pub fn dropout<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, p: f64, training: bool, rng: &mut Rng) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Dropout(*a.node_key(), p, training, RefCell::new(Rng::new(rng.next_u64())), E::dropout::<F>)))
+    }
+
+    pub fn where_<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, cond: &CompGraphTensor, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Where(*cond.node_key(), *a.node_key(), *b.node_key(), E::where_::<F>)))
+    }
+
+    pub fn clamp<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, min: Option<T>, max: Option<T>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Clamp(*a.node_key(), min, max, E::clamp::<F>)))
+    }
+
+    pub fn masked_fill<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, mask: &CompGraphTensor, value: T) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MaskedFill(*a.node_key(), *mask.node_key(), value, E::masked_fill::<F>)))
+    }
+
+    pub fn matmul<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, b: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MatMul(*a.node_key(), *b.node_key(), E::matmul::<F>)))
+    }
+
+    pub fn conv1d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: &CompGraphTensor, bias: Option<&CompGraphTensor>, params: ConvParams<1>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Conv1d(*a.node_key(), *kernel.node_key(), bias.map(|b| *b.node_key()), params, E::conv1d::<F>)))
+    }
+
+    pub fn conv2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: &CompGraphTensor, bias: Option<&CompGraphTensor>, params: ConvParams<2>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Conv2d(*a.node_key(), *kernel.node_key(), bias.map(|b| *b.node_key()), params, E::conv2d::<F>)))
+    }
+
+    pub fn conv3d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: &CompGraphTensor, bias: Option<&CompGraphTensor>, params: ConvParams<3>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Conv3d(*a.node_key(), *kernel.node_key(), bias.map(|b| *b.node_key()), params, E::conv3d::<F>)))
+    }
+
+    pub fn conv_transpose2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: &CompGraphTensor, bias: Option<&CompGraphTensor>, params: ConvParams<2>, output_padding: [usize; 2]) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::ConvTranspose2d(*a.node_key(), *kernel.node_key(), bias.map(|b| *b.node_key()), params, output_padding, E::conv_transpose2d::<F>)))
+    }
+
+    pub fn max_pool2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MaxPool2d(*a.node_key(), kernel, stride, padding, dilation, E::max_pool2d::<F>)))
+    }
+
+    //The indices come from Engine::max_pool2d_indices, usize tensors aren't part of the graph
+    pub fn max_unpool2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, indices: Box<dyn EngineTensor<Unit = usize>>, output_size: (usize, usize)) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MaxUnpool2d(*a.node_key(), indices, output_size, E::max_unpool2d::<F>)))
+    }
+
+    pub fn avg_pool2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize), count_include_pad: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::AvgPool2d(*a.node_key(), kernel, stride, padding, dilation, count_include_pad, E::avg_pool2d::<F>)))
+    }
+
+    pub fn adaptive_avg_pool2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, output_size: (usize, usize)) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::AdaptiveAvgPool2d(*a.node_key(), output_size, E::adaptive_avg_pool2d::<F>)))
+    }
+
+    pub fn adaptive_max_pool2d<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, output_size: (usize, usize)) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::AdaptiveMaxPool2d(*a.node_key(), output_size, E::adaptive_max_pool2d::<F>)))
+    }
+
+    pub fn batch_norm_no_running<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, weight: &CompGraphTensor, bias: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::BatchNormNoRunning(*a.node_key(), *weight.node_key(), *bias.node_key(), eps, E::batch_norm_no_running::<F>)))
+    }
+
+    pub fn batch_norm_running<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, running_mean: &CompGraphTensor, running_var: &CompGraphTensor, weight: &CompGraphTensor, bias: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::BatchNormRunning(*a.node_key(), *running_mean.node_key(), *running_var.node_key(), *weight.node_key(), *bias.node_key(), eps, E::batch_norm_running::<F>)))
+    }
+
+    pub fn layer_norm<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, normalized_shape: &Shape, weight: &CompGraphTensor, bias: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::LayerNorm(*a.node_key(), *weight.node_key(), *bias.node_key(), normalized_shape.clone(), eps, E::layer_norm::<F>)))
+    }
+
+    pub fn group_norm<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, groups: usize, weight: &CompGraphTensor, bias: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::GroupNorm(*a.node_key(), *weight.node_key(), *bias.node_key(), groups, eps, E::group_norm::<F>)))
+    }
+
+    pub fn instance_norm<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, weight: &CompGraphTensor, bias: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::InstanceNorm(*a.node_key(), *weight.node_key(), *bias.node_key(), eps, E::instance_norm::<F>)))
+    }
+
+    pub fn rms_norm<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, normalized_shape: &Shape, weight: &CompGraphTensor, eps: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::RmsNorm(*a.node_key(), *weight.node_key(), normalized_shape.clone(), eps, E::rms_norm::<F>)))
+    }
+
+    pub fn batch_norm_running_mean<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, running_mean: &CompGraphTensor, momentum: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::BatchNormRunningMean(*a.node_key(), *running_mean.node_key(), momentum, E::batch_norm_running_mean::<F>)))
+    }
+
+    pub fn batch_norm_running_var<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, running_var: &CompGraphTensor, momentum: f64) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::BatchNormRunningVar(*a.node_key(), *running_var.node_key(), momentum, E::batch_norm_running_var::<F>)))
+    }
+
+    pub fn sum<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axes: &[usize], keep_dims: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Sum(*a.node_key(), axes.into(), keep_dims, E::sum::<F>)))
+    }
+
+    pub fn mean<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axes: &[usize], keep_dims: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Mean(*a.node_key(), axes.into(), keep_dims, E::mean::<F>)))
+    }
+
+    pub fn max<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axes: &[usize], keep_dims: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Max(*a.node_key(), axes.into(), keep_dims, E::max::<F>)))
+    }
+
+    pub fn min<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axes: &[usize], keep_dims: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Min(*a.node_key(), axes.into(), keep_dims, E::min::<F>)))
+    }
+
+    pub fn product<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axes: &[usize], keep_dims: bool) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Product(*a.node_key(), axes.into(), keep_dims, E::product::<F>)))
+    }
+
+    pub fn softmax<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Softmax(*a.node_key(), axis, E::softmax::<F>)))
+    }
+
+    pub fn log_softmax<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::LogSoftmax(*a.node_key(), axis, E::log_softmax::<F>)))
+    }
+
+    pub fn concat<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, tensors: &[&CompGraphTensor], axis: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Concat(tensors.iter().map(|t| *t.node_key()).collect(), axis, E::concat::<F>)))
+    }
+
+    pub fn stack<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, tensors: &[&CompGraphTensor], axis: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Stack(tensors.iter().map(|t| *t.node_key()).collect(), axis, E::stack::<F>)))
+    }
+
+    pub fn permute<E: Engine<T>>(&mut self, a: &CompGraphTensor, dims: &[usize]) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Permute(*a.node_key(), dims.into(), E::permute)))
+    }
+
+    pub fn transpose<E: Engine<T>>(&mut self, a: &CompGraphTensor, dim0: usize, dim1: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Transpose(*a.node_key(), dim0, dim1, E::transpose)))
+    }
+
+    pub fn reshape<E: Engine<T>>(&mut self, a: &CompGraphTensor, shape: &Shape) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Reshape(*a.node_key(), shape.clone(), E::reshape)))
+    }
+
+    pub fn flatten<E: Engine<T>>(&mut self, a: &CompGraphTensor, start: usize, end: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Flatten(*a.node_key(), start, end, E::flatten)))
+    }
+
+    pub fn unflatten<E: Engine<T>>(&mut self, a: &CompGraphTensor, axis: usize, sizes: &[usize]) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Unflatten(*a.node_key(), axis, sizes.into(), E::unflatten)))
+    }
+
+    pub fn narrow<E: Engine<T>>(&mut self, a: &CompGraphTensor, axis: usize, start: usize, length: usize) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Narrow(*a.node_key(), axis, start, length, E::narrow)))
+    }
+
+    pub fn scaled_dot_product_attention<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, q: &CompGraphTensor, k: &CompGraphTensor, v: &CompGraphTensor, mask: Option<&CompGraphTensor>, causal_offset: Option<usize>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::ScaledDotProductAttention(*q.node_key(), *k.node_key(), *v.node_key(), mask.map(|m| *m.node_key()), causal_offset, E::scaled_dot_product_attention::<F>)))
+    }
+
+    pub fn embedding<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, weight: &CompGraphTensor, ids: Box<dyn EngineTensor<Unit = usize>>, padding_idx: Option<usize>, max_norm: Option<f64>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Embedding(*weight.node_key(), ids, padding_idx, max_norm, E::embedding::<F, usize>)))
+    }
+
+    pub fn embedding_bag<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, weight: &CompGraphTensor, ids: Box<dyn EngineTensor<Unit = usize>>, mode: EmbeddingBagMode, padding_idx: Option<usize>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::EmbeddingBag(*weight.node_key(), ids, mode, padding_idx, E::embedding_bag::<F, usize>)))
+    }
+
+    pub fn index_select<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize, indices: Box<dyn EngineTensor<Unit = usize>>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::IndexSelect(*a.node_key(), axis, indices, E::index_select::<F>)))
+    }
+
+    pub fn gather<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize, index: Box<dyn EngineTensor<Unit = usize>>) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Gather(*a.node_key(), axis, index, E::gather::<F>)))
+    }
+
+    pub fn scatter<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize, index: Box<dyn EngineTensor<Unit = usize>>, src: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::Scatter(*a.node_key(), axis, index, *src.node_key(), E::scatter::<F>)))
+    }
+
+    pub fn scatter_add<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, axis: usize, index: Box<dyn EngineTensor<Unit = usize>>, src: &CompGraphTensor) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::ScatterAdd(*a.node_key(), axis, index, *src.node_key(), E::scatter_add::<F>)))
+    }
+
+    pub fn mse_loss<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, target: &CompGraphTensor, reduction: Reduction) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::MseLoss(*a.node_key(), *target.node_key(), reduction, E::mse_loss::<F>)))
+    }
+
+    pub fn l1_loss<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, target: &CompGraphTensor, reduction: Reduction) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::L1Loss(*a.node_key(), *target.node_key(), reduction, E::l1_loss::<F>)))
+    }
+
+    pub fn huber_loss<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, target: &CompGraphTensor, delta: f64, reduction: Reduction) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::HuberLoss(*a.node_key(), *target.node_key(), delta, reduction, E::huber_loss::<F>)))
+    }
+
+    pub fn binary_cross_entropy_with_logits<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, target: &CompGraphTensor, reduction: Reduction) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::BinaryCrossEntropyWithLogits(*a.node_key(), *target.node_key(), reduction, E::binary_cross_entropy_with_logits::<F>)))
+    }
+
+    pub fn cross_entropy<E: Engine<T>, F: EngineTensorFactory<Unit = T>>(&mut self, a: &CompGraphTensor, target: &CompGraphTensor, label_smoothing: f64, reduction: Reduction) -> CompGraphTensor {
+        CompGraphTensor::new(self.create_node(Edge::CrossEntropy(*a.node_key(), *target.node_key(), label_smoothing, reduction, E::cross_entropy::<F>)))
+    }
+
+    //Shape of a tensor in the graph without computing it
+    pub fn shape(&self, tensor: &CompGraphTensor) -> Result<Shape, ComputationGraphError> {
+        let target = *tensor.node_key();
+        let shapes = self.infer_shapes(&self.topological_order(target)?)?;
+
+        match shapes.get(&target) {
+            Some(shape) => Ok(shape.clone()),
+            None => Ok(self.get_node_error(&target)?.tensor().ok_or(ComputationGraphError::NodeNotComputed(target))?.shape().clone()),
+        }
+    }
+
+    //Applies body to the carried state iterations times using a single node in this graph
+    //body is built in its own graph and is given placeholders for the carry and each capture
+    //The carry must keep its shape so the output has the same shape as init
+    pub fn scan<B: FnOnce(&mut CompGraph<T>, &CompGraphTensor, &[CompGraphTensor]) -> CompGraphTensor>(&mut self, init: &CompGraphTensor, captures: &[CompGraphTensor], iterations: usize, body: B) -> Result<CompGraphTensor, ComputationGraphError> {
+        let mut body_graph = CompGraph::<T>::new();
+
+        let carry = body_graph.create_placeholder();
+        let body_captures = captures.iter().map(|_| body_graph.create_placeholder()).collect::<Vec<_>>();
+
+        let output = body(&mut body_graph, &carry, &body_captures);
+
+        //The body is compiled once and reused for every iteration
+        let plan = body_graph.compile(&output)?;
+
+        let scan = Scan::new(
+            body_graph,
+            plan,
+            *carry.node_key(),
+            body_captures.iter().map(|c| *c.node_key()).collect(),
+            iterations,
+        );
+
+        Ok(CompGraphTensor::new(self.create_node(Edge::Scan(*init.node_key(), captures.iter().map(|c| *c.node_key()).collect(), Box::new(scan)))))
+    }
 }
 
 //External handle for nodes
@@ -312,6 +933,12 @@ pub enum ComputationGraphError {
     RootNodeIsChild(NodeKey),
     #[error("Tried to clear root node")]
     CannotClearRoot(),
+    #[error("Tried to replace the tensor of a non root node")]
+    CannotReplaceNonRoot(NodeKey),
+    #[error("Predicted peak of {0} bytes exceeds the budget of {1} bytes")]
+    MemoryBudgetExceeded(usize, usize),
+    #[error("Scan carry changed shape from {0} to {1}")]
+    ScanCarryShapeMismatch(Shape, Shape),
     #[error("Error in computation: {0}")]
     ComputationError(#[from]EngineError),
 }
@@ -459,4 +1086,123 @@ mod test {
 
         assert_eq!(*node.tensor().unwrap(), *expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn scan_matches_unrolled() {
+        let (node_key, _, _, mut graph) = init_complex_graph();
+
+        let divider = graph.create_root(Array::from_slice([2.0; 9].as_slice(), Shape::from([3, 3].as_slice())).generic());
+
+        let iterations = 16;
+
+        let mut unrolled = node_key.clone();
+        for _ in 0..iterations {
+            unrolled = graph.div::<Basic, Array<_>>(&unrolled, &divider);
+        }
+
+        let nodes_before_scan = graph.nodes.len();
+
+        let scanned = graph.scan(&node_key, &[divider], iterations, |body, carry, captures| {
+            body.div::<Basic, Array<_>>(carry, &captures[0])
+        }).unwrap();
+
+        assert_eq!(graph.nodes.len(), nodes_before_scan + 1);
+
+        graph.non_populating_eval(&unrolled).unwrap();
+        graph.non_populating_eval(&scanned).unwrap();
+
+        let unrolled_tensor = graph.get_node(unrolled.node_key()).unwrap().tensor().unwrap();
+        let scanned_tensor = graph.get_node(scanned.node_key()).unwrap().tensor().unwrap();
+
+        assert_eq!(unrolled_tensor, scanned_tensor);
+
+        graph.get_node_mut(scanned.node_key()).unwrap().clear_tensor().unwrap();
+        graph.populating_eval(&scanned).unwrap();
+
+        assert_eq!(graph.get_node(scanned.node_key()).unwrap().tensor().unwrap(), graph.get_node(unrolled.node_key()).unwrap().tensor().unwrap());
+    }
+
+    #[test]
+    fn compiled_plan_rerun() {
+        let (root1, root2, added, expected, mut graph) = init_simple_graph();
+
+        let doubled = graph.add::<Basic, Array<f32>>(&added, &added);
+        let out = graph.sub::<Basic, Array<f32>>(&doubled, &root2);
+
+        let plan = graph.compile(&out).unwrap();
+
+        assert_eq!(plan.steps().len(), 3);
+
+        //Computed parents are read from their slots, roots from the graph
+        let last = plan.steps().last().unwrap();
+
+        assert_eq!(last.node_key(), *out.node_key());
+        assert_eq!(last.operand_slot(*doubled.node_key()), Some(1));
+        assert_eq!(last.operand_slot(*root2.node_key()), None);
+
+        graph.run(&plan).unwrap();
+
+        let expected_out = Array::from_slice([0.0, 3.0, 6.0, 9.0].as_slice(), Shape::from([2, 2].as_slice())).generic();
+        assert_eq!(graph.get_node(out.node_key()).unwrap().tensor().unwrap(), expected_out.as_ref());
+
+        //Intermediates aren't stored in the graph
+        assert!(graph.get_node(added.node_key()).unwrap().tensor().is_none());
+
+        graph.replace_root(&root1, expected).unwrap();
+        graph.replace_root(&root2, Array::from_slice([1.0, 1.0, 1.0, 1.0].as_slice(), Shape::from([2, 2].as_slice())).generic()).unwrap();
+
+        graph.run(&plan).unwrap();
+
+        let expected_out = Array::from_slice([1.0, 5.0, 9.0, 13.0].as_slice(), Shape::from([2, 2].as_slice())).generic();
+        assert_eq!(graph.get_node(out.node_key()).unwrap().tensor().unwrap(), expected_out.as_ref());
+
+        assert!(graph.replace_root(&out, expected_out).is_err());
+    }
+
+    #[test]
+    fn compiled_dropout_rerun() {
+        let mut graph = CompGraph::<f32>::new();
+
+        let root = graph.create_root(Array::from_iter(std::iter::repeat_n(1.0, 64), Shape::from([64].as_slice())).generic());
+        let out = graph.dropout::<Basic, Array<f32>>(&root, 0.5, true, &mut Rng::new(3));
+
+        let plan = graph.compile(&out).unwrap();
+
+        graph.run(&plan).unwrap();
+        let first = graph.iter(&out).collect::<Vec<f32>>();
+
+        //Every run of the same plan draws a new mask
+        graph.run(&plan).unwrap();
+        let second = graph.iter(&out).collect::<Vec<f32>>();
+
+        assert!(first.iter().chain(second.iter()).all(|x| *x == 0.0 || *x == 2.0));
+        assert_ne!(first, second);
+
+        graph.non_populating_eval(&out).unwrap();
+        assert_ne!(graph.iter(&out).collect::<Vec<f32>>(), second);
+    }
+
+    #[test]
+    fn memory_planned_branches() {
+        let mut graph = CompGraph::<f32>::new();
+
+        let root = graph.create_root(Array::from_slice([1.0, -2.0, 3.0, -4.0].as_slice(), Shape::from([2, 2].as_slice())).generic());
+
+        let a1 = graph.abs::<Basic, Array<f32>>(&root);
+        let a2 = graph.neg::<Basic, Array<f32>>(&a1);
+        let b1 = graph.neg::<Basic, Array<f32>>(&root);
+        let b2 = graph.abs::<Basic, Array<f32>>(&b1);
+        let out = graph.add::<Basic, Array<f32>>(&a2, &b2);
+
+        //Each intermediate is 16 bytes, at most one branch result, the other branch's two tensors and the output are live
+        let plan = graph.compile_memory_planned(&out, None).unwrap();
+        assert_eq!(plan.predicted_peak_bytes(), Some(48));
+
+        assert!(matches!(graph.compile_memory_planned(&out, Some(40)), Err(ComputationGraphError::MemoryBudgetExceeded(48, 40))));
+
+        graph.run(&plan).unwrap();
+
+        let expected = Array::from_slice([0.0, 0.0, 0.0, 0.0].as_slice(), Shape::from([2, 2].as_slice())).generic();
+        assert_eq!(graph.get_node(out.node_key()).unwrap().tensor().unwrap(), expected.as_ref());
+    }
+}
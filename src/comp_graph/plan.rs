@@ -0,0 +1,132 @@
+use std::{collections::HashMap, rc::Rc};
+
+use slotmap::SecondaryMap;
+
+use crate::engine::unit::UnitCompatible;
+
+use super::{edge::Edge, ComputationGraphError, NodeKey};
+
+//A cached schedule for computing a target
+//Every computed node gets a buffer slot which is freed as soon as its last child has been computed
+//Steps hold their ops and operand slots so running doesn't look nodes up again, only roots are read from the graph
+#[derive(Debug)]
+pub struct ExecutionPlan<T: UnitCompatible> {
+    target: NodeKey,
+    steps: Box<[PlanStep<T>]>,
+    slots: SecondaryMap<NodeKey, usize>,
+    //Only known if node shapes were inferred when planning
+    predicted_peak_bytes: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct PlanStep<T: UnitCompatible> {
+    node_key: NodeKey,
+    edge: Rc<Edge<T>>,
+    //Parents computed by earlier steps and their slots, any other parent is a root
+    operands: Box<[(NodeKey, usize)]>,
+    slot: usize,
+    //Slots that are no longer needed once this step is done
+    frees: Box<[usize]>,
+}
+
+impl<T: UnitCompatible> ExecutionPlan<T> {
+    //order must be topological and only contain computed (non root) nodes
+    pub fn new<F: Fn(NodeKey) -> Result<Rc<Edge<T>>, ComputationGraphError>>(target: NodeKey, order: &[NodeKey], edges: F) -> Result<Self, ComputationGraphError> {
+        let slots = SecondaryMap::<NodeKey, usize>::from_iter(order.iter().enumerate().map(|(slot, k)| (*k, slot)));
+        let edges = order.iter().map(|k| edges(*k)).collect::<Result<Vec<_>, _>>()?;
+
+        let operands = edges.iter().map(|edge| {
+            edge.nodes().filter_map(|parent_key| slots.get(parent_key).map(|slot| (parent_key, *slot))).collect::<Box<[_]>>()
+        }).collect::<Vec<_>>();
+
+        //Index of the last step that reads each slot
+        let mut last_use = HashMap::<usize, usize>::new();
+
+        for (step_index, step_operands) in operands.iter().enumerate() {
+            for (_, parent_slot) in step_operands.iter() {
+                last_use.insert(*parent_slot, step_index);
+            }
+        }
+
+        let mut frees = vec![Vec::<usize>::new(); order.len()];
+
+        for (slot, step_index) in last_use {
+            //The target is never freed since it is the output
+            if order.get(slot) != Some(&target) {
+                frees.get_mut(step_index).unwrap().push(slot);
+            }
+        }
+
+        let steps = order.iter().zip(edges).zip(operands).zip(frees).enumerate().map(|(slot, (((node_key, edge), operands), frees))| PlanStep {
+            node_key: *node_key,
+            edge,
+            operands,
+            slot,
+            frees: frees.into_boxed_slice(),
+        }).collect();
+
+        Ok(Self {
+            target,
+            steps,
+            slots,
+            predicted_peak_bytes: None,
+        })
+    }
+
+    //Simulates the plan, an output is allocated while its parents are still live
+    pub fn with_predicted_peak<F: Fn(NodeKey) -> usize>(mut self, node_bytes: F) -> Self {
+        let mut live = 0usize;
+        let mut peak = 0usize;
+
+        for step in self.steps.iter() {
+            live += node_bytes(step.node_key);
+            peak = peak.max(live);
+
+            for slot in step.frees.iter() {
+                live -= node_bytes(self.steps.get(*slot).unwrap().node_key);
+            }
+        }
+
+        self.predicted_peak_bytes = Some(peak);
+        self
+    }
+
+    pub fn predicted_peak_bytes(&self) -> Option<usize> {
+        self.predicted_peak_bytes
+    }
+
+    pub fn target(&self) -> NodeKey {
+        self.target
+    }
+
+    pub fn steps(&self) -> &[PlanStep<T>] {
+        &self.steps
+    }
+
+    pub fn slot(&self, node_key: NodeKey) -> Option<usize> {
+        self.slots.get(node_key).copied()
+    }
+}
+
+impl<T: UnitCompatible> PlanStep<T> {
+    pub fn node_key(&self) -> NodeKey {
+        self.node_key
+    }
+
+    pub fn edge(&self) -> &Edge<T> {
+        &self.edge
+    }
+
+    //The slot a parent was computed into, None for roots
+    pub fn operand_slot(&self, parent_key: NodeKey) -> Option<usize> {
+        self.operands.iter().find(|(k, _)| *k == parent_key).map(|(_, slot)| *slot)
+    }
+
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    pub fn frees(&self) -> &[usize] {
+        &self.frees
+    }
+}
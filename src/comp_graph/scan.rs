@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use crate::engine::{tensor::EngineTensor, unit::UnitCompatible};
+
+use super::{CompGraph, ComputationGraphError, ExecutionPlan, NodeKey};
+
+//A body subgraph that is applied repeatedly to a carried state
+//Intermediate nodes only exist in the body graph so the size of the outer graph doesn't depend on iterations
+#[derive(Debug)]
+pub struct Scan<T: UnitCompatible> {
+    body: CompGraph<T>,
+    //Targets the output which becomes the carry for the next iteration
+    plan: ExecutionPlan<T>,
+
+    //Placeholders in the body graph
+    carry: NodeKey,
+    captures: Box<[NodeKey]>,
+
+    iterations: usize,
+}
+
+impl<T: UnitCompatible> Scan<T> {
+    pub fn new(body: CompGraph<T>, plan: ExecutionPlan<T>, carry: NodeKey, captures: Box<[NodeKey]>, iterations: usize) -> Self {
+        Self {
+            body,
+            plan,
+            carry,
+            captures,
+            iterations,
+        }
+    }
+
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    pub fn run(&self, init: &dyn EngineTensor<Unit = T>, captures: &[&dyn EngineTensor<Unit = T>]) -> Result<Box<dyn EngineTensor<Unit = T>>, ComputationGraphError> {
+        let bound_captures = HashMap::<NodeKey, &dyn EngineTensor<Unit = T>>::from_iter(self.captures.iter().copied().zip(captures.iter().copied()));
+
+        let mut carry = init.clone();
+
+        for _ in 0..self.iterations {
+            let mut bound = bound_captures.clone();
+            bound.insert(self.carry, carry.as_ref());
+
+            let next = self.body.run_bound(&self.plan, &bound)?;
+
+            if next.shape() != carry.shape() {
+                return Err(ComputationGraphError::ScanCarryShapeMismatch(carry.shape().clone(), next.shape().clone()));
+            }
+
+            carry = next;
+        }
+
+        Ok(carry)
+    }
+}
@@ -1,7 +1,7 @@
 pub mod tensor;
 pub mod unit;
 
-use crate::helper::{PositionError, Shape, VarArrayError};
+use crate::helper::{PositionError, Rng, Shape, VarArrayError};
 use self::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible};
 use thiserror::Error;
 
@@ -17,22 +17,270 @@ pub trait Engine<T: UnitCompatible> {
     fn relu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
     fn leaky_relu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, alpha: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
     fn sigmoid<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn silu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn gelu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Approximates gelu with tanh
+    fn gelu_tanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn mish<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn selu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn hardswish<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn elu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, alpha: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Reverts to the identity where a * beta > threshold
+    fn softplus<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, beta: f64, threshold: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn hardtanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, min_val: f64, max_val: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //weight: (1) shared by every channel or (channels) where a: (batches, channels, ...)
+    fn prelu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Gen
+    fn sqrt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn rsqrt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn reciprocal<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Exponential, log is the natural logarithm
+    fn exp<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn expm1<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn log<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn log1p<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Trig
+    fn sin<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn cos<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn tan<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn sinh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn cosh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn tanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn asin<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn acos<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn atan<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn asinh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn acosh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn atanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Rounding, halfway values round to even
+    fn floor<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn ceil<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn round<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn sign<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Pointwise Scalar
+    fn add_scalar<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn sub_scalar_lh<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn sub_scalar_rh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn mul_scalar<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn div_scalar_lh<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn div_scalar_rh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //a ^ s
+    fn pow_scalar<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
 
     //Pointwise Double
     fn add<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
     fn sub<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
     fn mul<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
     fn div<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //a ^ b
+    fn pow<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn maximum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn minimum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Comparison
+    //Units are one where the comparison holds and zero otherwise
+    fn eq<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn ne<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn lt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn le<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn gt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn ge<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Selection
+    //Any non-zero unit of cond selects from a, otherwise from b
+    fn where_<E: EngineTensorFactory<Unit = T>>(cond: &dyn EngineTensor<Unit = T>, a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Either bound can be left out
+    fn clamp<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, min: Option<T>, max: Option<T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //mask must broadcast to the shape of a, non-zero units of mask are replaced with value
+    fn masked_fill<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, mask: &dyn EngineTensor<Unit = T>, value: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Reduction
+    //Empty axes reduce over every axis, reducing every axis without keep_dims gives shape (1)
+    fn sum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn mean<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn max<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn min<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn product<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Indices of the first max/min along the axis, usize units so they can be given to gather and index_select
+    //Max, min and their indices error when reducing over an empty dimension
+    fn argmax<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, axis: usize, keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError>;
+    fn argmin<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, axis: usize, keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError>;
+
+    //Softmax
+    //Normalised along axis, the shape is unchanged
+    fn softmax<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn log_softmax<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Join and split
+    //Every tensor must match apart from along axis
+    fn concat<E: EngineTensorFactory<Unit = T>>(tensors: &[&dyn EngineTensor<Unit = T>], axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Every tensor must match, joined along a new axis
+    fn stack<E: EngineTensorFactory<Unit = T>>(tensors: &[&dyn EngineTensor<Unit = T>], axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Split and chunk return slices of a so nothing is copied
+    //sizes must add up to the dimension of axis
+    fn split(a: &dyn EngineTensor<Unit = T>, sizes: &[usize], axis: usize) -> Result<Vec<Box<dyn EngineTensor<Unit = T>>>, EngineError>;
+    //Every chunk but the last has ceil(dim / chunks) along axis, there may be fewer chunks than requested
+    fn chunk(a: &dyn EngineTensor<Unit = T>, chunks: usize, axis: usize) -> Result<Vec<Box<dyn EngineTensor<Unit = T>>>, EngineError>;
+
+    //Reorder
+    //Returned as views of a, see EngineTensor::permute
+    fn permute(a: &dyn EngineTensor<Unit = T>, dims: &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn transpose(a: &dyn EngineTensor<Unit = T>, dim0: usize, dim1: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Views fall back to copying when a isn't contiguous
+    fn reshape(a: &dyn EngineTensor<Unit = T>, shape: &Shape) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Merges the dimensions start to end (inclusive) into one
+    fn flatten(a: &dyn EngineTensor<Unit = T>, start: usize, end: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Splits the dimension at axis into sizes
+    fn unflatten(a: &dyn EngineTensor<Unit = T>, axis: usize, sizes: &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //length units of the dimension at axis starting from start
+    fn narrow(a: &dyn EngineTensor<Unit = T>, axis: usize, start: usize, length: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Attention
+    //q: (*, target_len, embed), k: (*, source_len, embed), v: (*, source_len, value_embed), out: (*, target_len, value_embed)
+    //mask broadcasts to (*, target_len, source_len), queries only attend to keys where the mask is non-zero
+    //With a causal offset query i only attends to keys up to i + offset, Some(0) is PyTorch's is_causal (top left aligned)
+    //Queries that can't attend to any key give zeros
+    fn scaled_dot_product_attention<E: EngineTensorFactory<Unit = T>>(q: &dyn EngineTensor<Unit = T>, k: &dyn EngineTensor<Unit = T>, v: &dyn EngineTensor<Unit = T>, mask: Option<&dyn EngineTensor<Unit = T>>, causal_offset: Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Indexing
+    //indices: (n), the output has n along axis
+    fn index_select<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, indices: &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //index has as many dimensions as a and the output takes its shape
+    //out[i][j] = a[index[i][j]][j] for axis = 0
+    fn gather<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Copy of a with out[index[i][j]][j] = src[i][j] for axis = 0, repeated indices keep the last write
+    fn scatter<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>, src: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Same as scatter but repeated indices accumulate
+    fn scatter_add<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>, src: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Embedding
+    //ids can have any unit that casts to usize, so integer units can be used directly
+    //weight: (num_embeddings, embedding_dim), ids: (*), out: (*, embedding_dim)
+    //Ids must be non-negative integers, so float units like 1.5 or -1.0 are rejected
+    //padding_idx only stops gradients in PyTorch, so its row is returned as is
+    //Rows with a norm over max_norm are rescaled to max_norm (weight itself isn't changed)
+    fn embedding<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(weight: &dyn EngineTensor<Unit = T>, ids: &dyn EngineTensor<Unit = I>, padding_idx: Option<usize>, max_norm: Option<f64>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //ids: (bags, bag_len), out: (bags, embedding_dim)
+    //Ids of padding_idx are left out of their bag, empty bags give zeros
+    fn embedding_bag<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(weight: &dyn EngineTensor<Unit = T>, ids: &dyn EngineTensor<Unit = I>, mode: EmbeddingBagMode, padding_idx: Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //ids: (*), out: (*, num_classes)
+    fn one_hot<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(ids: &dyn EngineTensor<Unit = I>, num_classes: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
 
     fn matmul<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
 
     //Conv
-    fn conv2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, padding: usize, stride: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
-    //fn im2col_2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel_shape: &Shape, padding: usize, stride: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //a: (batches, in_channels, *spatial)
+    //kernel: (out_channels, in_channels / groups, *kernel_spatial), bias: (out_channels)
+    //Channels are split into groups that are convolved separately, groups = in_channels gives a depthwise convolution
+    fn conv1d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<1>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn conv2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<2>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn conv3d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<3>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //a: (batches, in_channels, y, x)
+    //kernel: (in_channels, out_channels / groups, k_y, k_x), bias: (out_channels)
+    //Gradient of conv2d with respect to its input, output_padding picks between the output sizes that conv2d maps to the same input size
+    fn conv_transpose2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<2>, output_padding: [usize; 2]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
 
     //Pool
-    fn batch_norm_no_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError>;
-    fn batch_norm_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, momentum: f64, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError>;
+    //a: (batches, channels, y, x), window parameters are (y, x)
+    //Padding can be at most half the kernel and is never picked as a max
+    fn max_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Index of each max in the flattened (y, x) plane of a
+    fn max_pool2d_indices<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError>;
+    //Places each unit at its index from max_pool2d_indices, everything else is zero
+    fn max_unpool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, indices: &dyn EngineTensor<Unit = usize>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //count_include_pad divides by the full kernel size instead of the units inside a
+    fn avg_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize), count_include_pad: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Windows are spread evenly over a to give output_size
+    fn adaptive_avg_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn adaptive_max_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Norm
+    //a: (batches, num_features, *), weight, bias and running statistics: (num_features)
+    //Normalises each feature with the mean and biased variance of the batch (training)
+    fn batch_norm_no_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Normalises each feature with the running statistics (evaluation)
+    fn batch_norm_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Running statistics after seeing the batch: (1 - momentum) * running + momentum * batch
+    //The variance uses the unbiased variance of the batch
+    fn batch_norm_running_mean<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, momentum: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn batch_norm_running_var<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, momentum: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Each sample is normalised over its trailing normalized_shape dimensions, weight and bias: normalized_shape
+    fn layer_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, normalized_shape: &Shape, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //a: (batches, num_features, *), weight and bias: (num_features)
+    //The features of each sample are split into groups which are each normalised over their units
+    fn group_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, groups: usize, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Group norm with a group per feature
+    fn instance_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //x / sqrt(mean(x^2) + eps) * weight over the trailing normalized_shape dimensions, weight: normalized_shape
+    fn rms_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, normalized_shape: &Shape, weight: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Random
+    //Each call advances rng, so results only depend on the seed and the order of calls
+    fn rand_uniform<E: EngineTensorFactory<Unit = T>>(shape: &Shape, low: f64, high: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn rand_normal<E: EngineTensorFactory<Unit = T>>(shape: &Shape, mean: f64, std: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Units are one with probability p, otherwise zero
+    fn bernoulli<E: EngineTensorFactory<Unit = T>>(shape: &Shape, p: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //Units are in [low, high)
+    fn randint<E: EngineTensorFactory<Unit = T>>(shape: &Shape, low: usize, high: usize, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //While training units are zeroed with probability p and the rest are scaled by 1 / (1 - p), otherwise a copy of a
+    fn dropout<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, p: f64, training: bool, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+
+    //Loss
+    //Reduced losses have shape (1)
+    fn mse_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn l1_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn huber_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, delta: f64, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    fn binary_cross_entropy_with_logits<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+    //a: (batches, classes, *)
+    //target: (batches, *) holding class indices
+    fn cross_entropy<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, label_smoothing: f64, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    None,
+    Mean,
+    Sum,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmbeddingBagMode {
+    Sum,
+    Mean,
+}
+
+//Stride, padding and dilation have one entry per spatial axis
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConvParams<const N: usize> {
+    pub stride: [usize; N],
+    pub padding: [usize; N],
+    pub dilation: [usize; N],
+    pub groups: usize,
+}
+
+impl<const N: usize> ConvParams<N> {
+    pub fn new(stride: [usize; N], padding: [usize; N], dilation: [usize; N], groups: usize) -> Self {
+        Self {
+            stride,
+            padding,
+            dilation,
+            groups,
+        }
+    }
+}
+
+//Unit stride and dilation, no padding and a single group
+impl<const N: usize> Default for ConvParams<N> {
+    fn default() -> Self {
+        Self::new([1; N], [0; N], [1; N], 1)
+    }
 }
 
 #[derive(Error, Debug)]
@@ -51,7 +299,31 @@ pub enum EngineError {
     VarArray(#[from] VarArrayError),
     #[error("Position operation failed: {0}")]
     Tensor(#[from] PositionError),
+    #[error("The index {0} is out of range for a dimension of size {1}")]
+    IndexOutOfRange(usize, usize),
+    #[error("The unit {0} is not a non-negative integer so can't be used as an index")]
+    InvalidIndexUnit(String),
+    #[error("The window of size {0} does not fit in a dimension of size {1}")]
+    WindowTooLarge(usize, usize),
+    #[error("The kernel size {0}, stride {1} and dilation {2} must all be more than zero")]
+    ZeroWindow(usize, usize, usize),
+    #[error("The padding {0} is more than half the kernel size {1}")]
+    PaddingTooLarge(usize, usize),
+    #[error("{0} groups do not evenly divide {1} channels")]
+    GroupsMismatch(usize, usize),
     #[error("The operation is not supported on this data type")]
     OperationUnsupportedForType(),
+    #[error("The tensor of shape {0} is empty along a reduced dimension")]
+    EmptyReduction(Shape),
+    #[error("At least one tensor is required")]
+    NoTensors(),
+    #[error("Cannot split into {0} chunks")]
+    InvalidChunks(usize),
+    #[error("The dimensions {0:?} are not a permutation of {1} dimensions")]
+    InvalidPermutation(Box<[usize]>, usize),
+    #[error("The probability {0} is not between 0 and 1")]
+    InvalidProbability(f64),
+    #[error("The range {0}..{1} is empty")]
+    EmptyRange(usize, usize),
 }
 
@@ -4,12 +4,12 @@ pub mod builder;
 pub mod factory;
 pub mod sub_tensor_iter;
 
-use crate::helper::{Interval, Position, Shape};
+use crate::helper::{Interval, Position, Shape, VarArrayCompatible};
 use self::extension::ExtensionProvider;
 use self::unit_iter::EngineTensorUnitIterator;
 use self::sub_tensor_iter::EngineTensorSubTensorIterator;
 use std::fmt::Debug;
-use super::unit::UnitCompatible;
+use super::{unit::UnitCompatible, EngineError};
 
 //Unless otherwise specified every function should make as shallow of a copy as possible
 pub trait EngineTensor: Debug {
@@ -27,6 +27,39 @@ pub trait EngineTensor: Debug {
     fn reshape(&self, shape: &Shape) -> Box<dyn EngineTensor<Unit = Self::Unit>>;
     fn trim(&self) -> Box<dyn EngineTensor<Unit = Self::Unit>>;
     fn broadcast_splice(&self, pos: usize, sub: &[usize]) -> Box<dyn EngineTensor<Unit = Self::Unit>>;
+    //Expands to a shape produced by Shape::broadcast_with without copying where possible
+    //Errors if self can't be broadcast to shape
+    fn broadcast_to(&self, shape: &Shape) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError>;
+    //Dimension i of the output is dimension dims[i] of self
+    //Errors unless dims holds every dimension of self exactly once
+    fn permute(&self, dims: &[usize]) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError>;
+
+    fn transpose(&self, a: usize, b: usize) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        let num_dims = self.shape().len();
+
+        for dim in [a, b] {
+            if dim >= num_dims {
+                return Err(EngineError::IndexOutOfRange(dim, num_dims));
+            }
+        }
+
+        let mut dims = (0..num_dims).collect::<Vec<_>>();
+        dims.swap(a, b);
+
+        self.permute(&dims)
+    }
+
+    //Transposes the matrices of a batch of matrices
+    //Tensors with fewer than 2 dimensions are returned as they are, like transposing a vector
+    fn swap_last_two(&self) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        let dims = self.shape().len();
+
+        if dims >= 2 {
+            self.transpose(dims - 2, dims - 1)
+        } else {
+            Ok(self.clone())
+        }
+    }
 
     fn extensions(&self) -> Box<dyn ExtensionProvider + '_>;
 }
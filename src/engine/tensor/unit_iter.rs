@@ -50,11 +50,14 @@ pub struct EngineTensorUnitIterator<'a, T: UnitCompatible> {
 
 impl<'a, T: UnitCompatible> EngineTensorUnitIterator<'a, T> {
     pub fn new(tensor: &'a dyn EngineTensor<Unit = T>) -> Self {
+        //A tensor with a dimension of 0 has no last position and nothing to iterate
+        let empty = tensor.shape().elements() == 0;
+
         Self {
             tensor,
             curr: tensor.shape().first(),
-            finish: tensor.shape().last(),
-            ended: false,
+            finish: if empty { tensor.shape().first() } else { tensor.shape().last() },
+            ended: empty,
         }
     }
 }
@@ -1,8 +1,11 @@
-use std::{iter::{Product, Sum}, ops::{Add, Div, Mul, Rem, Sub}};
+use std::{f64::consts::{FRAC_1_SQRT_2, FRAC_2_SQRT_PI, PI}, iter::{Product, Sum}, ops::{Add, Div, Mul, Rem, Sub}};
 
-use super::{core_value::CoreValue, exponential_op::ExponentialOp, scale::Scale, Base};
+use super::{core_value::CoreValue, exponential_op::ExponentialOp, scale::Scale, signed_op::SignedOp, Base};
 
-pub trait CoreFunc: CoreValue + ExponentialOp + Scale + Add<Output = Self>
+const SELU_ALPHA: f64 = 1.6732632423543772848170429916717;
+const SELU_SCALE: f64 = 1.0507009873554804934193349852946;
+
+pub trait CoreFunc: CoreValue + ExponentialOp + SignedOp + Scale + Add<Output = Self>
 + Sub<Output = Self>
 + Mul<Output = Self>
 + Div<Output = Self>
@@ -19,14 +22,64 @@ pub trait CoreFunc: CoreValue + ExponentialOp + Scale + Add<Output = Self>
     fn leaky_relu(self, alpha: f64) -> Self {
         if self > Self::zero() { self } else { self.scale_double(alpha) }
     }
+    fn prelu(self, weight: Self) -> Self {
+        if self > Self::zero() { self } else { weight * self }
+    }
+    //Exp is only given non-positive values so large inputs can't overflow
     fn sigmoid(self) -> Self {
-        let exp = self.exp();
+        if self >= Self::zero() {
+            Self::one() / (Self::one() + self.neg().exp())
+        } else {
+            let exp = self.exp();
 
-        exp / (Self::one() + exp)
+            exp / (Self::one() + exp)
+        }
+    }
+    fn silu(self) -> Self {
+        self * self.sigmoid()
+    }
+    fn gelu(self) -> Self {
+        self.scale_double(0.5) * (Self::one() + self.scale_double(FRAC_1_SQRT_2).erf())
+    }
+    fn gelu_tanh(self) -> Self {
+        let inner = (self + (self * self * self).scale_double(0.044715)).scale_double(FRAC_2_SQRT_PI * FRAC_1_SQRT_2);
+
+        self.scale_double(0.5) * (Self::one() + inner.tanh())
+    }
+    fn elu(self, alpha: f64) -> Self {
+        if self > Self::zero() { self } else { self.exp_m1().scale_double(alpha) }
+    }
+    fn selu(self) -> Self {
+        self.elu(SELU_ALPHA).scale_double(SELU_SCALE)
+    }
+    //Reverts to linear past the threshold, otherwise max(x, 0) + ln(1 + exp(-|x|)) which can't overflow
+    fn softplus(self, beta: f64, threshold: f64) -> Self {
+        let scaled = self.scale_double(beta);
+
+        if scaled > Self::one().scale_double(threshold) {
+            self
+        } else {
+            let negative_abs = if scaled > Self::zero() { scaled.neg() } else { scaled };
+
+            (scaled.relu() + negative_abs.exp().ln_1p()).scale_double(1.0 / beta)
+        }
+    }
+    fn mish(self) -> Self {
+        self * self.softplus(1.0, 20.0).tanh()
+    }
+    fn hardtanh(self, min: f64, max: f64) -> Self {
+        let min = Self::one().scale_double(min);
+        let max = Self::one().scale_double(max);
+
+        if self < min { min } else if self > max { max } else { self }
+    }
+    fn hardswish(self) -> Self {
+        self * (self + Self::one().scale_double(3.0)).hardtanh(0.0, 6.0).scale_double(1.0 / 6.0)
     }
 
     //Gen
     fn sqrt(self) -> Self;
+    fn erf(self) -> Self;
 
     //Trig
     fn sin(self) -> Self;
@@ -44,6 +97,49 @@ pub trait CoreFunc: CoreValue + ExponentialOp + Scale + Add<Output = Self>
     fn asinh(self) -> Self;
     fn acosh(self) -> Self;
     fn atanh(self) -> Self;
+
+    //Rounding
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    //Halfway values round to even
+    fn round(self) -> Self;
+}
+
+//The standard library's erf is still unstable
+//Taylor series near zero, continued fraction for erfc in the tails where the series loses precision
+fn erf(x: f64) -> f64 {
+    if x.is_nan() {
+        return x;
+    }
+
+    let a = x.abs();
+
+    let res = if a < 2.5 {
+        let sq = a * a;
+
+        let mut term = a;
+        let mut sum = a;
+
+        for n in 1..80 {
+            term *= -sq / n as f64;
+
+            let add = term / (2 * n + 1) as f64;
+            sum += add;
+
+            if add.abs() < 1e-17 * sum.abs() {
+                break;
+            }
+        }
+
+        sum * FRAC_2_SQRT_PI
+    } else {
+        //erfc(a) = exp(-a^2) / sqrt(pi) / (a + (1/2) / (a + 1 / (a + (3/2) / (a + ...))))
+        let frac = (1..60).rev().fold(a, |frac, n| a + (n as f64 / 2.0) / frac);
+
+        1.0 - (-a * a).exp() / (frac * PI.sqrt())
+    };
+
+    res.copysign(x)
 }
 
 macro_rules! core_func_float {
@@ -53,6 +149,10 @@ macro_rules! core_func_float {
                 self.sqrt()
             }
 
+            fn erf(self) -> Self {
+                erf(self as f64) as $unit
+            }
+
             fn sin(self) -> Self {
                 self.sin()
             }
@@ -100,6 +200,18 @@ macro_rules! core_func_float {
             fn atanh(self) -> Self {
                 self.atanh()
             }
+
+            fn floor(self) -> Self {
+                self.floor()
+            }
+
+            fn ceil(self) -> Self {
+                self.ceil()
+            }
+
+            fn round(self) -> Self {
+                self.round_ties_even()
+            }
         }
     };
 }
@@ -111,6 +223,10 @@ macro_rules! core_func_int {
                 (self as f64).sqrt() as $unit
             }
 
+            fn erf(self) -> Self {
+                erf(self as f64) as $unit
+            }
+
             fn sin(self) -> Self {
                 (self as f64).sin() as $unit
             }
@@ -158,6 +274,19 @@ macro_rules! core_func_int {
             fn atanh(self) -> Self {
                 (self as f64).atanh() as $unit
             }
+
+            //Integers are already whole
+            fn floor(self) -> Self {
+                self
+            }
+
+            fn ceil(self) -> Self {
+                self
+            }
+
+            fn round(self) -> Self {
+                self
+            }
         }
     };
 }
@@ -2,6 +2,12 @@ use super::Base;
 
 pub trait ExponentialOp: Base {
     fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn pow(self, exponent: Self) -> Self;
+
+    //More accurate than exp(x) - 1 and ln(1 + x) near zero
+    fn exp_m1(self) -> Self;
+    fn ln_1p(self) -> Self;
 }
 
 macro_rules! exponential_op_int {
@@ -10,6 +16,22 @@ macro_rules! exponential_op_int {
             fn exp(self) -> Self {
                 (self as f64).exp() as $unit
             }
+
+            fn ln(self) -> Self {
+                (self as f64).ln() as $unit
+            }
+
+            fn pow(self, exponent: Self) -> Self {
+                (self as f64).powf(exponent as f64) as $unit
+            }
+
+            fn exp_m1(self) -> Self {
+                (self as f64).exp_m1() as $unit
+            }
+
+            fn ln_1p(self) -> Self {
+                (self as f64).ln_1p() as $unit
+            }
         }
     };
 }
@@ -18,12 +40,44 @@ impl ExponentialOp for f32 {
     fn exp(self) -> Self {
         self.exp()
     }
+
+    fn ln(self) -> Self {
+        self.ln()
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        self.powf(exponent)
+    }
+
+    fn exp_m1(self) -> Self {
+        self.exp_m1()
+    }
+
+    fn ln_1p(self) -> Self {
+        self.ln_1p()
+    }
 }
 
 impl ExponentialOp for f64 {
     fn exp(self) -> Self {
         self.exp()
     }
+
+    fn ln(self) -> Self {
+        self.ln()
+    }
+
+    fn pow(self, exponent: Self) -> Self {
+        self.powf(exponent)
+    }
+
+    fn exp_m1(self) -> Self {
+        self.exp_m1()
+    }
+
+    fn ln_1p(self) -> Self {
+        self.ln_1p()
+    }
 }
 
 exponential_op_int!(i8);
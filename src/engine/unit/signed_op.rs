@@ -3,6 +3,26 @@ use super::Base;
 pub trait SignedOp: Base {
     fn abs(self) -> Self;
     fn neg(self) -> Self;
+    //-1, 0 or 1, zero keeps its sign for floats
+    fn sign(self) -> Self;
+}
+
+macro_rules! signed_op_float {
+    ($unit:ty) => {
+        impl SignedOp for $unit {
+            fn abs(self) -> Self {
+                self.abs()
+            }
+
+            fn neg(self) -> Self {
+                -self
+            }
+
+            fn sign(self) -> Self {
+                if self == 0.0 { self } else { self.signum() }
+            }
+        }
+    };
 }
 
 macro_rules! signed_op_signed {
@@ -15,6 +35,10 @@ macro_rules! signed_op_signed {
             fn neg(self) -> Self {
                 -self
             }
+
+            fn sign(self) -> Self {
+                self.signum()
+            }
         }
     };
 }
@@ -29,12 +53,16 @@ macro_rules! signed_op_unsigned {
             fn neg(self) -> Self {
                 self
             }
+
+            fn sign(self) -> Self {
+                if self == 0 { 0 } else { 1 }
+            }
         }
     };
 }
 
-signed_op_signed!{f32}
-signed_op_signed!{f64}
+signed_op_float!{f32}
+signed_op_float!{f64}
 
 signed_op_signed!{i8}
 signed_op_signed!{i16}
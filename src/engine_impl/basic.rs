@@ -2,7 +2,7 @@ use std::iter;
 
 use itertools::Itertools;
 
-use crate::{engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible, Engine, EngineError}, engine_impl::{shared::im2col_2d, util::{err_if_dimension_mismatch, err_if_dimensions_mistmatch, err_if_incorrect_num_dimensions, err_if_too_few_dimensions}}, helper::{shape, varr, Interval, Shape, VarArray, VarArrayCompatible}};
+use crate::{engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible, ConvParams, EmbeddingBagMode, Engine, EngineError, Reduction}, engine_impl::{shared::{adaptive_pool_window, attention_blocked, axis_index_positions, bool_unit, broadcast_pair, broadcast_zip, channel_parameter, channel_statistics, conv, conv_transpose, embedding_rows, non_channel_axes, normalise_channels, pool_2d, pool_window, reduce_axes, reduce_loss, scatter_with, softmax_axis}, util::{adaptive_pool_2d_shape, attention_shape, concat_shape, embedding_bag_shape, embedding_shape, err_if_dimension_mismatch, err_if_invalid_probability, err_if_dimensions_mistmatch, err_if_incorrect_num_dimensions, err_if_invalid_padding_idx, err_if_too_few_dimensions, flatten_shape, index_select_shape, narrow_shape, normalized_axes, pool_2d_shape, reshape_shape, return_if_matched_shape, stack_shape, unflatten_shape, unit_to_index}}, helper::{shape, varr, Interval, Rng, Shape, VarArray, VarArrayCompatible}};
 use crate::engine::tensor::builder::EngineTensorBuilder;
 pub struct Basic {}
 
@@ -29,37 +29,527 @@ impl<T: UnitCompatible> Engine<T> for Basic {
         Ok(E::from_iter(a.iter_units().map(|x: T| x.sigmoid()), a.shape().clone()).generic())
     }
 
+    fn silu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.silu()), a.shape().clone()).generic())
+    }
+
+    fn gelu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.gelu()), a.shape().clone()).generic())
+    }
+
+    fn gelu_tanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.gelu_tanh()), a.shape().clone()).generic())
+    }
+
+    fn mish<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.mish()), a.shape().clone()).generic())
+    }
+
+    fn selu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.selu()), a.shape().clone()).generic())
+    }
+
+    fn hardswish<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.hardswish()), a.shape().clone()).generic())
+    }
+
+    fn elu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, alpha: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.elu(alpha)), a.shape().clone()).generic())
+    }
+
+    fn softplus<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, beta: f64, threshold: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.softplus(beta, threshold)), a.shape().clone()).generic())
+    }
+
+    fn hardtanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, min_val: f64, max_val: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.hardtanh(min_val, max_val)), a.shape().clone()).generic())
+    }
+
+    fn prelu<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let weights = weight.iter_units().collect::<Vec<_>>();
+
+        if weights.len() == 1 {
+            return Ok(E::from_iter(a.iter_units().map(|x| x.prelu(weights[0])), a.shape().clone()).generic());
+        }
+
+        err_if_too_few_dimensions(a.shape(), 2)?;
+        err_if_dimension_mismatch(weights.len(), a.shape().get(1)?)?;
+
+        let channel_len = a.shape().iter().skip(2).product::<usize>();
+
+        Ok(E::from_iter(a.iter_units().enumerate().map(|(i, x)| x.prelu(weights[(i / channel_len) % weights.len()])), a.shape().clone()).generic())
+    }
+
+    //Gen
+    fn sqrt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.sqrt()), a.shape().clone()).generic())
+    }
+
+    fn rsqrt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| T::one() / x.sqrt()), a.shape().clone()).generic())
+    }
+
+    fn reciprocal<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| T::one() / x), a.shape().clone()).generic())
+    }
+
+    //Exponential
+    fn exp<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.exp()), a.shape().clone()).generic())
+    }
+
+    fn expm1<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.exp_m1()), a.shape().clone()).generic())
+    }
+
+    fn log<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.ln()), a.shape().clone()).generic())
+    }
+
+    fn log1p<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.ln_1p()), a.shape().clone()).generic())
+    }
+
+    //Trig
+    fn sin<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.sin()), a.shape().clone()).generic())
+    }
+
+    fn cos<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.cos()), a.shape().clone()).generic())
+    }
+
+    fn tan<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.tan()), a.shape().clone()).generic())
+    }
+
+    fn sinh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.sinh()), a.shape().clone()).generic())
+    }
+
+    fn cosh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.cosh()), a.shape().clone()).generic())
+    }
+
+    fn tanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.tanh()), a.shape().clone()).generic())
+    }
+
+    fn asin<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.asin()), a.shape().clone()).generic())
+    }
+
+    fn acos<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.acos()), a.shape().clone()).generic())
+    }
+
+    fn atan<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.atan()), a.shape().clone()).generic())
+    }
+
+    fn asinh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.asinh()), a.shape().clone()).generic())
+    }
+
+    fn acosh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.acosh()), a.shape().clone()).generic())
+    }
+
+    fn atanh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.atanh()), a.shape().clone()).generic())
+    }
+
+    //Rounding
+    fn floor<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.floor()), a.shape().clone()).generic())
+    }
+
+    fn ceil<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.ceil()), a.shape().clone()).generic())
+    }
+
+    fn round<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.round()), a.shape().clone()).generic())
+    }
+
+    fn sign<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.sign()), a.shape().clone()).generic())
+    }
+
+    //Pointwise Scalar
+    fn add_scalar<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| s + x), a.shape().clone()).generic())
+    }
+
+    fn sub_scalar_lh<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| s - x), a.shape().clone()).generic())
+    }
+
+    fn sub_scalar_rh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x - s), a.shape().clone()).generic())
+    }
+
+    fn mul_scalar<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| s * x), a.shape().clone()).generic())
+    }
+
+    fn div_scalar_lh<E: EngineTensorFactory<Unit = T>>(s: T, a: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| s / x), a.shape().clone()).generic())
+    }
+
+    fn div_scalar_rh<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x / s), a.shape().clone()).generic())
+    }
+
+    fn pow_scalar<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, s: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| x.pow(s)), a.shape().clone()).generic())
+    }
+
     //Pointwise Double
     fn add<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        if a.shape() == b.shape() {
-            Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x + y), a.shape().clone()).generic())
-        } else {
-            Err(EngineError::ShapeMismatch(a.shape().clone(), b.shape().clone()))
-        }
+        let (a, b) = broadcast_pair(a, b)?;
+
+        Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x + y), a.shape().clone()).generic())
     }
 
     fn sub<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        if a.shape() == b.shape() {
-            Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x - y), a.shape().clone()).generic())
-        } else {
-            Err(EngineError::ShapeMismatch(a.shape().clone(), b.shape().clone()))
-        }
+        let (a, b) = broadcast_pair(a, b)?;
+
+        Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x - y), a.shape().clone()).generic())
     }
 
     fn mul<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        if a.shape() == b.shape() {
-            Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x * y), a.shape().clone()).generic())
-        } else {
-            Err(EngineError::ShapeMismatch(a.shape().clone(), b.shape().clone()))
-        }
+        let (a, b) = broadcast_pair(a, b)?;
+
+        Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x * y), a.shape().clone()).generic())
     }
 
     fn div<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        if a.shape() == b.shape() {
-            Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x / y), a.shape().clone()).generic())
-        } else {
-            Err(EngineError::ShapeMismatch(a.shape().clone(), b.shape().clone()))
+        let (a, b) = broadcast_pair(a, b)?;
+
+        Ok(E::from_iter(&mut a.iter_units().zip(b.iter_units()).map(|(x, y)| x / y), a.shape().clone()).generic())
+    }
+
+    fn pow<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let (a, b) = broadcast_pair(a, b)?;
+
+        Ok(E::from_iter(a.iter_units().zip(b.iter_units()).map(|(x, y)| x.pow(y)), a.shape().clone()).generic())
+    }
+
+    fn maximum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| if x >= y { x } else { y })
+    }
+
+    fn minimum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| if x <= y { x } else { y })
+    }
+
+    //Comparison
+    fn eq<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x == y))
+    }
+
+    fn ne<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x != y))
+    }
+
+    fn lt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x < y))
+    }
+
+    fn le<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x <= y))
+    }
+
+    fn gt<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x > y))
+    }
+
+    fn ge<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        broadcast_zip::<T, E>(a, b, |x, y| bool_unit(x >= y))
+    }
+
+    //Selection
+    fn where_<E: EngineTensorFactory<Unit = T>>(cond: &dyn EngineTensor<Unit = T>, a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = cond.shape().broadcast_with(a.shape()).and_then(|s| s.broadcast_with(b.shape()));
+        let out_shape = out_shape.ok_or_else(|| EngineError::ShapeMismatch(cond.shape().clone(), a.shape().clone()))?;
+
+        let (cond, a, b) = (cond.broadcast_to(&out_shape)?, a.broadcast_to(&out_shape)?, b.broadcast_to(&out_shape)?);
+
+        Ok(E::from_iter(cond.iter_units().zip(a.iter_units().zip(b.iter_units())).map(|(c, (x, y))| if c != T::zero() { x } else { y }), out_shape).generic())
+    }
+
+    fn clamp<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, min: Option<T>, max: Option<T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter(a.iter_units().map(|x| {
+            let x = match min { Some(min) if x < min => min, _ => x };
+
+            match max { Some(max) if x > max => max, _ => x }
+        }), a.shape().clone()).generic())
+    }
+
+    fn masked_fill<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, mask: &dyn EngineTensor<Unit = T>, value: T) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let mask = mask.broadcast_to(a.shape())?;
+
+        Ok(E::from_iter(a.iter_units().zip(mask.iter_units()).map(|(x, m)| if m != T::zero() { value } else { x }), a.shape().clone()).generic())
+    }
+
+    //Reduction
+    fn sum<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reduce_axes::<T, E>(a, axes, keep_dims, |units| Some(units.sum::<T>()))
+    }
+
+    fn mean<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reduce_axes::<T, E>(a, axes, keep_dims, |units| {
+            let (count, sum) = units.fold((0, T::zero()), |(count, sum), x| (count + 1, sum + x));
+
+            Some(sum / T::from(count))
+        })
+    }
+
+    fn max<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reduce_axes::<T, E>(a, axes, keep_dims, |units| units.reduce(|m, x| if x > m { x } else { m }))
+    }
+
+    fn min<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reduce_axes::<T, E>(a, axes, keep_dims, |units| units.reduce(|m, x| if x < m { x } else { m }))
+    }
+
+    fn product<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reduce_axes::<T, E>(a, axes, keep_dims, |units| Some(units.product::<T>()))
+    }
+
+    fn argmax<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, axis: usize, keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError> {
+        reduce_axes::<T, E>(a, &[axis], keep_dims, |units| {
+            units.enumerate().reduce(|m, x| if x.1 > m.1 { x } else { m }).map(|(i, _)| i)
+        })
+    }
+
+    fn argmin<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, axis: usize, keep_dims: bool) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError> {
+        reduce_axes::<T, E>(a, &[axis], keep_dims, |units| {
+            units.enumerate().reduce(|m, x| if x.1 < m.1 { x } else { m }).map(|(i, _)| i)
+        })
+    }
+
+    //Softmax
+    fn softmax<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        softmax_axis::<T, E>(a, axis, |x, max, sum| (x - max).exp() / sum)
+    }
+
+    fn log_softmax<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        softmax_axis::<T, E>(a, axis, |x, max, sum| x - max - sum.ln())
+    }
+
+    //Join and split
+    fn concat<E: EngineTensorFactory<Unit = T>>(tensors: &[&dyn EngineTensor<Unit = T>], axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = concat_shape(&tensors.iter().map(|t| t.shape()).collect::<Vec<_>>(), axis)?;
+
+        let mut builder = E::builder(out_shape.clone(), T::zero());
+        let mut intervals = iter::repeat(Interval::all()).take(out_shape.len()).collect::<Vec<_>>();
+
+        let mut start = 0;
+
+        for tensor in tensors {
+            let dim = tensor.shape().get(axis)?;
+
+            *intervals.get_mut(axis).unwrap() = Interval::between(start, start + dim);
+            builder.splice_slice(&intervals, tensor.iter_units());
+
+            start += dim;
+        }
+
+        Ok(builder.construct().generic())
+    }
+
+    fn stack<E: EngineTensorFactory<Unit = T>>(tensors: &[&dyn EngineTensor<Unit = T>], axis: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = stack_shape(&tensors.iter().map(|t| t.shape()).collect::<Vec<_>>(), axis)?;
+
+        //Each tensor gets a dimension of 1 at axis so they can be concatenated
+        let unsqueezed_shape = Shape::from_iter(out_shape.iter().enumerate().map(|(i, d)| if i == axis { 1 } else { d }));
+        let unsqueezed = tensors.iter().map(|t| t.reshape(&unsqueezed_shape)).collect::<Vec<_>>();
+
+        Self::concat::<E>(&unsqueezed.iter().map(|t| t.as_ref()).collect::<Vec<_>>(), axis)
+    }
+
+    fn split(a: &dyn EngineTensor<Unit = T>, sizes: &[usize], axis: usize) -> Result<Vec<Box<dyn EngineTensor<Unit = T>>>, EngineError> {
+        err_if_too_few_dimensions(a.shape(), axis + 1)?;
+        err_if_dimension_mismatch(sizes.iter().sum(), a.shape().get(axis)?)?;
+
+        let mut intervals = iter::repeat(Interval::all()).take(a.shape().len()).collect::<Vec<_>>();
+
+        Ok(sizes.iter().scan(0, |start, size| {
+            *intervals.get_mut(axis).unwrap() = Interval::between(*start, *start + size);
+            *start += size;
+
+            Some(a.slice(&intervals))
+        }).collect())
+    }
+
+    fn chunk(a: &dyn EngineTensor<Unit = T>, chunks: usize, axis: usize) -> Result<Vec<Box<dyn EngineTensor<Unit = T>>>, EngineError> {
+        if chunks == 0 {
+            return Err(EngineError::InvalidChunks(chunks));
+        }
+
+        err_if_too_few_dimensions(a.shape(), axis + 1)?;
+
+        let dim = a.shape().get(axis)?;
+
+        //An empty axis still gives one (empty) chunk
+        if dim == 0 {
+            return Self::split(a, &[0], axis);
+        }
+
+        let chunk_size = dim.div_ceil(chunks);
+
+        let sizes = (0..dim).step_by(chunk_size).map(|start| chunk_size.min(dim - start)).collect::<Vec<_>>();
+
+        Self::split(a, &sizes, axis)
+    }
+
+    //Reorder
+    fn permute(a: &dyn EngineTensor<Unit = T>, dims: &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        a.permute(dims)
+    }
+
+    fn transpose(a: &dyn EngineTensor<Unit = T>, dim0: usize, dim1: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        a.transpose(dim0, dim1)
+    }
+
+    fn reshape(a: &dyn EngineTensor<Unit = T>, shape: &Shape) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        reshape_shape(a.shape(), shape)?;
+
+        Ok(a.reshape(shape))
+    }
+
+    fn flatten(a: &dyn EngineTensor<Unit = T>, start: usize, end: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(a.reshape(&flatten_shape(a.shape(), start, end)?))
+    }
+
+    fn unflatten(a: &dyn EngineTensor<Unit = T>, axis: usize, sizes: &[usize]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(a.reshape(&unflatten_shape(a.shape(), axis, sizes)?))
+    }
+
+    fn narrow(a: &dyn EngineTensor<Unit = T>, axis: usize, start: usize, length: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        narrow_shape(a.shape(), axis, start, length)?;
+
+        let intervals = (0..a.shape().len()).map(|d| if d == axis { Interval::between(start, start + length) } else { Interval::all() }).collect::<Vec<_>>();
+
+        Ok(a.slice(&intervals))
+    }
+
+    //Attention
+    fn scaled_dot_product_attention<E: EngineTensorFactory<Unit = T>>(q: &dyn EngineTensor<Unit = T>, k: &dyn EngineTensor<Unit = T>, v: &dyn EngineTensor<Unit = T>, mask: Option<&dyn EngineTensor<Unit = T>>, causal_offset: Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        attention_shape(q.shape(), k.shape(), v.shape())?;
+
+        let embed = q.shape().get(q.shape().len() - 1)?;
+        let k_t = k.swap_last_two()?;
+
+        let scale = 1.0 / (embed as f64).sqrt();
+        let scores = Self::matmul::<E>(q, k_t.as_ref())?;
+        let scores = E::from_iter(scores.iter_units().map(|x| x.scale_double(scale)), scores.shape().clone()).generic();
+
+        let mask = mask.map(|mask| mask.broadcast_to(scores.shape())).transpose()?;
+        let blocked = attention_blocked::<T, E>(scores.shape(), mask.as_deref(), causal_offset)?;
+
+        //Scaling one by -inf gives -inf for floats and the lowest value of integers
+        let filled = Self::masked_fill::<E>(scores.as_ref(), blocked.as_ref(), T::one().scale_double(f64::NEG_INFINITY))?;
+        let weights = Self::softmax::<E>(filled.as_ref(), scores.shape().len() - 1)?;
+
+        //Queries with every key blocked softmax to NaN, zeroing the blocked keys leaves them with no weight at all
+        let weights = Self::masked_fill::<E>(weights.as_ref(), blocked.as_ref(), T::zero())?;
+
+        Self::matmul::<E>(weights.as_ref(), v)
+    }
+
+    //Indexing
+    fn index_select<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, indices: &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = index_select_shape(a.shape(), axis, indices.shape())?;
+        let axis_dim = a.shape().get(axis)?;
+
+        let mut builder = E::builder(out_shape.clone(), T::zero());
+
+        let mut a_intervals = iter::repeat(Interval::all()).take(out_shape.len()).collect::<Vec<_>>();
+        let mut out_intervals = a_intervals.clone();
+
+        for (i, index) in indices.iter_units().enumerate() {
+            if index >= axis_dim {
+                return Err(EngineError::IndexOutOfRange(index, axis_dim));
+            }
+
+            *a_intervals.get_mut(axis).unwrap() = Interval::only(index);
+            *out_intervals.get_mut(axis).unwrap() = Interval::only(i);
+
+            builder.splice_slice(&out_intervals, a.slice(&a_intervals).iter_units());
+        }
+
+        Ok(builder.construct().generic())
+    }
+
+    fn gather<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let positions = axis_index_positions(a.shape(), axis, index)?;
+        let units = a.iter_units().collect::<Vec<_>>();
+
+        Ok(E::from_iter(positions.into_iter().map(|p| units[p]), index.shape().clone()).generic())
+    }
+
+    fn scatter<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>, src: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        scatter_with::<T, E>(a, axis, index, src, |_, x| x)
+    }
+
+    fn scatter_add<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, axis: usize, index: &dyn EngineTensor<Unit = usize>, src: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        scatter_with::<T, E>(a, axis, index, src, |current, x| current + x)
+    }
+
+    //Embedding
+    fn embedding<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(weight: &dyn EngineTensor<Unit = T>, ids: &dyn EngineTensor<Unit = I>, padding_idx: Option<usize>, max_norm: Option<f64>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = embedding_shape(weight.shape(), ids.shape())?;
+
+        //Only checked, the row of padding_idx is looked up like any other
+        err_if_invalid_padding_idx(padding_idx, weight.shape().get(0)?)?;
+
+        let rows = embedding_rows(weight, ids, None, max_norm)?;
+        let units = rows.into_iter().flatten().flatten();
+
+        Ok(E::from_iter(units, out_shape).generic())
+    }
+
+    fn embedding_bag<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(weight: &dyn EngineTensor<Unit = T>, ids: &dyn EngineTensor<Unit = I>, mode: EmbeddingBagMode, padding_idx: Option<usize>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = embedding_bag_shape(weight.shape(), ids.shape())?;
+        let embedding_dim = weight.shape().get(1)?;
+        let bag_len = ids.shape().get(1)?;
+
+        let rows = embedding_rows(weight, ids, padding_idx, None)?;
+        let mut units = Vec::with_capacity(out_shape.elements());
+
+        for bag in rows.chunks(bag_len.max(1)) {
+            let members = bag.iter().flatten().collect::<Vec<_>>();
+
+            let sums = (0..embedding_dim).map(|d| members.iter().map(|row| row[d]).sum::<T>());
+
+            match mode {
+                EmbeddingBagMode::Sum => units.extend(sums),
+                EmbeddingBagMode::Mean if members.is_empty() => units.extend(sums),
+                EmbeddingBagMode::Mean => units.extend(sums.map(|x| x / T::from(members.len()))),
+            }
+        }
+
+        Ok(E::from_slice(&units, out_shape).generic())
+    }
+
+    fn one_hot<E: EngineTensorFactory<Unit = T>, I: UnitCompatible>(ids: &dyn EngineTensor<Unit = I>, num_classes: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = Shape::from_iter(ids.shape().iter().chain([num_classes]));
+        let mut units = Vec::with_capacity(out_shape.elements());
+
+        for id in ids.iter_units().map(unit_to_index) {
+            let id = id?;
+
+            if id >= num_classes {
+                return Err(EngineError::IndexOutOfRange(id, num_classes));
+            }
+
+            units.extend((0..num_classes).map(|class| bool_unit::<T>(class == id)));
         }
+
+        Ok(E::from_slice(&units, out_shape).generic())
     }
 
     fn matmul<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, b: &dyn EngineTensor<Unit = T>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
@@ -135,100 +625,450 @@ impl<T: UnitCompatible> Engine<T> for Basic {
     }
 
     //Conv
-    //a: (batches, in_channels, y, x)
-    //kernel: (out_channels, in_channels, k_y, k_x)
-    fn conv2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, padding: usize, stride: usize) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        err_if_incorrect_num_dimensions(a.shape(), 4)?;
-        err_if_incorrect_num_dimensions(kernel.shape(), 4)?;
-        err_if_dimension_mismatch(a.shape().get(1).unwrap(), kernel.shape().get(1).unwrap())?;
-
-        let batches = a.shape().get(0).unwrap();
-        let in_channels = a.shape().get(1).unwrap();
-
-        let out_channels = kernel.shape().get(0).unwrap();
-        let k_y = kernel.shape().get(2).unwrap();
-        let k_x = kernel.shape().get(3).unwrap();
+    fn conv1d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<1>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        conv::<T, E, 1>(a, kernel, bias, &params)
+    }
 
-        //(batches, out_channels, in_channels, out_y, out_x, patch_len)
-        let proc = im2col_2d::<T, E>(a, kernel.shape(), padding, stride).broadcast_splice(1, &[out_channels]);
+    fn conv2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<2>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        conv::<T, E, 2>(a, kernel, bias, &params)
+    }
 
-        let out_y = proc.shape().get(3).unwrap();
-        let out_x = proc.shape().get(4).unwrap();
-        let patch_len = proc.shape().get(5).unwrap();
+    fn conv3d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<3>) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        conv::<T, E, 3>(a, kernel, bias, &params)
+    }
 
-        //(batches, out_channels, in_channels, out_y, out_x, patch_len)
-        let kernels = kernel.reshape(&shape![out_channels, in_channels, k_y * k_x]).broadcast_splice(0, &[batches]).broadcast_splice(3, [out_y, out_x].as_slice());
-        
-        println!("{:?}", out_y);
-        println!("{:?}", proc.shape());
-        println!("{:?}", kernels.shape());
+    fn conv_transpose2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: &dyn EngineTensor<Unit = T>, bias: Option<&dyn EngineTensor<Unit = T>>, params: ConvParams<2>, output_padding: [usize; 2]) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        conv_transpose::<T, E, 2>(a, kernel, bias, &params, &output_padding)
+    }
 
-        let chunked_iter = proc.iter_units().zip(kernels.iter_units()).map(|(x, y)| x * y).chunks(patch_len);
-        let out_data = chunked_iter.into_iter().map(|i| i.sum());
+    //Pool
+    fn max_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = pool_2d_shape(a.shape(), kernel, stride, padding, dilation)?;
+        let (img_y, img_x) = (a.shape().get(2)?, a.shape().get(3)?);
+
+        pool_2d::<T, E>(
+            a,
+            (out_shape.get(2)?, out_shape.get(3)?),
+            |y| pool_window(y, img_y, kernel.0, stride.0, padding.0, dilation.0),
+            |x| pool_window(x, img_x, kernel.1, stride.1, padding.1, dilation.1),
+            |units| units.map(|(_, x)| x).reduce(|m, x| if x > m { x } else { m }).unwrap_or(T::zero()),
+        )
+    }
 
-        //(batches, out_channels, out_y, out_x)
-        Ok(E::from_iter(out_data, shape![batches, out_channels, out_y, out_x]).generic())
+    fn max_pool2d_indices<E: EngineTensorFactory<Unit = usize>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = usize>>, EngineError> {
+        let out_shape = pool_2d_shape(a.shape(), kernel, stride, padding, dilation)?;
+        let (img_y, img_x) = (a.shape().get(2)?, a.shape().get(3)?);
+
+        pool_2d::<T, E>(
+            a,
+            (out_shape.get(2)?, out_shape.get(3)?),
+            |y| pool_window(y, img_y, kernel.0, stride.0, padding.0, dilation.0),
+            |x| pool_window(x, img_x, kernel.1, stride.1, padding.1, dilation.1),
+            |units| units.reduce(|m, x| if x.1 > m.1 { x } else { m }).map(|(i, _)| i).unwrap_or(0),
+        )
     }
-    
-    fn batch_norm_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, momentum: f64, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        let num_features = a.shape().get(1)?;
 
-        err_if_dimension_mismatch(running_mean.shape().get(0)?, num_features)?;
-        err_if_dimension_mismatch(running_var.shape().get(0)?, num_features)?;
-        err_if_dimension_mismatch(weight.shape().get(0)?, num_features)?;
-        err_if_dimension_mismatch(bias.shape().get(0)?, num_features)?;
+    fn max_unpool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, indices: &dyn EngineTensor<Unit = usize>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_incorrect_num_dimensions(a.shape(), 4)?;
+        return_if_matched_shape(indices.shape(), a.shape(), ())?;
 
-        todo!()
-    }
-    
-    fn batch_norm_no_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, crate::engine::EngineError> {
-        let num_features = a.shape().get(1)?;
+        let (out_y, out_x) = output_size;
+        let plane = out_y * out_x;
+        let planes = a.shape().get(0)? * a.shape().get(1)?;
+        let units_per_plane = a.shape().get(2)? * a.shape().get(3)?;
 
-        err_if_dimension_mismatch(weight.shape().get(0)?, num_features)?;
-        err_if_dimension_mismatch(bias.shape().get(0)?, num_features)?;
+        let mut buffer = vec![T::zero(); planes * plane];
 
-        let mut builder = E::builder(a.shape().clone(), T::zero());
+        for (i, (x, index)) in a.iter_units().zip(indices.iter_units()).enumerate() {
+            if index >= plane {
+                return Err(EngineError::IndexOutOfRange(index, plane));
+            }
 
-        let mut channels_intervals = iter::repeat(Interval::all()).take(a.shape().len() - 2).collect::<Vec<_>>();
-        let channel_index = 1;
+            *buffer.get_mut((i / units_per_plane) * plane + index).unwrap() = x;
+        }
 
-        for channel in 0..num_features {
-            *channels_intervals.get_mut(channel_index).unwrap() = Interval::only(channel);
+        Ok(E::from_slice(&buffer, Shape::from([a.shape().get(0)?, a.shape().get(1)?, out_y, out_x].as_slice())).generic())
+    }
 
-            let channel_slice = a.slice(&channels_intervals);
+    fn avg_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize), count_include_pad: bool) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let out_shape = pool_2d_shape(a.shape(), kernel, stride, padding, dilation)?;
+        let (img_y, img_x) = (a.shape().get(2)?, a.shape().get(3)?);
+
+        pool_2d::<T, E>(
+            a,
+            (out_shape.get(2)?, out_shape.get(3)?),
+            |y| pool_window(y, img_y, kernel.0, stride.0, padding.0, dilation.0),
+            |x| pool_window(x, img_x, kernel.1, stride.1, padding.1, dilation.1),
+            |units| {
+                let (count, sum) = units.fold((0, T::zero()), |(count, sum), (_, x)| (count + 1, sum + x));
+
+                if count_include_pad {
+                    sum / T::from(kernel.0 * kernel.1)
+                } else {
+                    sum / T::from(count.max(1))
+                }
+            },
+        )
+    }
 
-            let channel_sum: T = channel_slice.iter_units().sum();
-            let channel_elements = T::from(channel_slice.shape().elements());
+    fn adaptive_avg_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        adaptive_pool_2d_shape(a.shape(), output_size)?;
+        let (img_y, img_x) = (a.shape().get(2)?, a.shape().get(3)?);
+
+        pool_2d::<T, E>(
+            a,
+            output_size,
+            |y| adaptive_pool_window(y, img_y, output_size.0),
+            |x| adaptive_pool_window(x, img_x, output_size.1),
+            |units| {
+                let (count, sum) = units.fold((0, T::zero()), |(count, sum), (_, x)| (count + 1, sum + x));
+
+                sum / T::from(count.max(1))
+            },
+        )
+    }
 
-            let channel_mean = channel_sum / channel_elements; 
-            let channel_variance =  channel_slice.iter_units().map(|x| (x - channel_mean) * (x - channel_mean)).sum::<T>() / channel_elements;
+    fn adaptive_max_pool2d<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, output_size: (usize, usize)) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        adaptive_pool_2d_shape(a.shape(), output_size)?;
+        let (img_y, img_x) = (a.shape().get(2)?, a.shape().get(3)?);
+
+        pool_2d::<T, E>(
+            a,
+            output_size,
+            |y| adaptive_pool_window(y, img_y, output_size.0),
+            |x| adaptive_pool_window(x, img_x, output_size.1),
+            |units| units.map(|(_, x)| x).reduce(|m, x| if x > m { x } else { m }).unwrap_or(T::zero()),
+        )
+    }
 
-            let norms = channel_slice.iter_units().zip(weight.iter_units()).zip(bias.iter_units()).map(|((x, w), b)| {
-                ((x - channel_mean) / ((channel_variance).sqrt())) * w + b // + eps
-            });
+    fn batch_norm_no_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let axes = non_channel_axes(a.shape())?;
+        let (means, variances, _) = channel_statistics(a, &axes, true)?;
 
-            builder.splice_slice(&channels_intervals, norms);
-        }
+        normalise_channels::<T, E>(a, &axes, &means, &variances, channel_parameter(weight, a.shape())?.as_ref(), channel_parameter(bias, a.shape())?.as_ref(), eps)
+    }
 
-        Ok(builder.construct().generic())
+    fn batch_norm_running<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let axes = non_channel_axes(a.shape())?;
+        let num_features = a.shape().get(1)?;
+
+        err_if_dimension_mismatch(running_mean.shape().get(0)?, num_features)?;
+        err_if_dimension_mismatch(running_var.shape().get(0)?, num_features)?;
+
+        let means = running_mean.iter_units().collect::<Vec<_>>();
+        let variances = running_var.iter_units().collect::<Vec<_>>();
+
+        normalise_channels::<T, E>(a, &axes, &means, &variances, channel_parameter(weight, a.shape())?.as_ref(), channel_parameter(bias, a.shape())?.as_ref(), eps)
+    }
+
+    fn batch_norm_running_mean<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_mean: &dyn EngineTensor<Unit = T>, momentum: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let (means, _, _) = channel_statistics(a, &non_channel_axes(a.shape())?, true)?;
+
+        err_if_dimension_mismatch(running_mean.shape().get(0)?, means.len())?;
+
+        let updated = running_mean.iter_units().zip(means).map(|(running, batch)| running.scale_double(1.0 - momentum) + batch.scale_double(momentum));
+
+        Ok(E::from_iter(updated, running_mean.shape().clone()).generic())
+    }
+
+    fn batch_norm_running_var<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, running_var: &dyn EngineTensor<Unit = T>, momentum: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let (_, variances, elements) = channel_statistics(a, &non_channel_axes(a.shape())?, true)?;
+
+        err_if_dimension_mismatch(running_var.shape().get(0)?, variances.len())?;
+
+        //Bessel's correction
+        let correction = elements as f64 / (elements.max(2) - 1) as f64;
+
+        let updated = running_var.iter_units().zip(variances).map(|(running, batch)| running.scale_double(1.0 - momentum) + batch.scale_double(correction * momentum));
+
+        Ok(E::from_iter(updated, running_var.shape().clone()).generic())
+    }
+
+    fn layer_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, normalized_shape: &Shape, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let axes = normalized_axes(a.shape(), normalized_shape)?;
+
+        return_if_matched_shape(weight.shape(), normalized_shape, ())?;
+        return_if_matched_shape(bias.shape(), normalized_shape, ())?;
+
+        let (means, variances, _) = channel_statistics(a, &axes, true)?;
+
+        normalise_channels::<T, E>(a, &axes, &means, &variances, weight, bias, eps)
+    }
+
+    fn group_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, groups: usize, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_too_few_dimensions(a.shape(), 2)?;
+
+        let batches = a.shape().get(0)?;
+        let channels = a.shape().get(1)?;
+
+        if groups == 0 || channels % groups != 0 {
+            return Err(EngineError::GroupsMismatch(groups, channels));
+        }
+
+        err_if_incorrect_num_dimensions(weight.shape(), 1)?;
+        err_if_incorrect_num_dimensions(bias.shape(), 1)?;
+        err_if_dimension_mismatch(weight.shape().get(0)?, channels)?;
+        err_if_dimension_mismatch(bias.shape().get(0)?, channels)?;
+
+        //Viewed as (batches, groups, channels per group, units per channel) so every group is reduced over its last two axes
+        let rest = a.shape().iter().skip(2).product::<usize>();
+        let grouped = a.reshape(&shape![batches, groups, channels / groups, rest]);
+        let affine_shape = shape![groups, channels / groups, 1];
+
+        let (means, variances, _) = channel_statistics(grouped.as_ref(), &[2, 3], true)?;
+        let norms = normalise_channels::<T, E>(grouped.as_ref(), &[2, 3], &means, &variances, weight.reshape(&affine_shape).as_ref(), bias.reshape(&affine_shape).as_ref(), eps)?;
+
+        Ok(norms.reshape(a.shape()))
+    }
+
+    fn instance_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, weight: &dyn EngineTensor<Unit = T>, bias: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_too_few_dimensions(a.shape(), 2)?;
+
+        Self::group_norm::<E>(a, a.shape().get(1)?, weight, bias, eps)
+    }
+
+    fn rms_norm<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, normalized_shape: &Shape, weight: &dyn EngineTensor<Unit = T>, eps: f64) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        let axes = normalized_axes(a.shape(), normalized_shape)?;
+
+        return_if_matched_shape(weight.shape(), normalized_shape, ())?;
+
+        let (means, variances, _) = channel_statistics(a, &axes, false)?;
+
+        normalise_channels::<T, E>(a, &axes, &means, &variances, weight, E::from_slice(&[T::zero()], shape![1]).generic().as_ref(), eps)
+    }
+
+    //Random
+    fn rand_uniform<E: EngineTensorFactory<Unit = T>>(shape: &Shape, low: f64, high: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter((0..shape.elements()).map(|_| T::one().scale_double(rng.uniform(low, high))), shape.clone()).generic())
+    }
+
+    fn rand_normal<E: EngineTensorFactory<Unit = T>>(shape: &Shape, mean: f64, std: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        Ok(E::from_iter((0..shape.elements()).map(|_| T::one().scale_double(rng.normal(mean, std))), shape.clone()).generic())
+    }
+
+    fn bernoulli<E: EngineTensorFactory<Unit = T>>(shape: &Shape, p: f64, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_invalid_probability(p)?;
+
+        Ok(E::from_iter((0..shape.elements()).map(|_| bool_unit(rng.bernoulli(p))), shape.clone()).generic())
+    }
+
+    fn randint<E: EngineTensorFactory<Unit = T>>(shape: &Shape, low: usize, high: usize, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        if low >= high {
+            return Err(EngineError::EmptyRange(low, high));
+        }
+
+        Ok(E::from_iter((0..shape.elements()).map(|_| T::from(low + rng.below((high - low) as u64) as usize)), shape.clone()).generic())
+    }
+
+    fn dropout<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, p: f64, training: bool, rng: &mut Rng) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_invalid_probability(p)?;
+
+        if !training {
+            return Ok(E::from_iter(a.iter_units(), a.shape().clone()).generic());
+        }
+
+        //Everything is dropped when p is one so the scale is never used
+        let scale = if p < 1.0 { 1.0 / (1.0 - p) } else { 0.0 };
+
+        Ok(E::from_iter(a.iter_units().map(|x| if rng.bernoulli(p) { T::zero() } else { x.scale_double(scale) }), a.shape().clone()).generic())
+    }
+
+    //Loss
+    fn mse_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        return_if_matched_shape(a.shape(), target.shape(), ())?;
+
+        let losses = a.iter_units().zip(target.iter_units()).map(|(x, y)| (x - y) * (x - y));
+
+        Ok(reduce_loss::<T, E>(losses, a.shape().clone(), reduction))
+    }
+
+    fn l1_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        return_if_matched_shape(a.shape(), target.shape(), ())?;
+
+        let losses = a.iter_units().zip(target.iter_units()).map(|(x, y)| (x - y).abs());
+
+        Ok(reduce_loss::<T, E>(losses, a.shape().clone(), reduction))
+    }
+
+    fn huber_loss<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, delta: f64, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        return_if_matched_shape(a.shape(), target.shape(), ())?;
+
+        let delta_unit = T::one().scale_double(delta);
+
+        let losses = a.iter_units().zip(target.iter_units()).map(|(x, y)| {
+            let diff = (x - y).abs();
+
+            if diff < delta_unit {
+                (diff * diff).scale_double(0.5)
+            } else {
+                (diff - delta_unit.scale_double(0.5)) * delta_unit
+            }
+        });
+
+        Ok(reduce_loss::<T, E>(losses, a.shape().clone(), reduction))
+    }
+
+    //max(x, 0) - x * y + ln(1 + exp(-|x|)) avoids overflowing exp for large logits
+    fn binary_cross_entropy_with_logits<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        return_if_matched_shape(a.shape(), target.shape(), ())?;
+
+        let losses = a.iter_units().zip(target.iter_units()).map(|(x, y)| {
+            x.relu() - x * y + (T::one() + x.abs().neg().exp()).ln()
+        });
+
+        Ok(reduce_loss::<T, E>(losses, a.shape().clone(), reduction))
+    }
+
+    //Uses log-sum-exp with the max subtracted for each set of class scores
+    fn cross_entropy<E: EngineTensorFactory<Unit = T>>(a: &dyn EngineTensor<Unit = T>, target: &dyn EngineTensor<Unit = T>, label_smoothing: f64, reduction: Reduction) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+        err_if_too_few_dimensions(a.shape(), 2)?;
+
+        let batches = a.shape().get(0)?;
+        let classes = a.shape().get(1)?;
+
+        let target_shape = Shape::from_iter(a.shape().iter().enumerate().filter_map(|(i, d)| if i == 1 { None } else { Some(d) }));
+        return_if_matched_shape(target.shape(), &target_shape, ())?;
+
+        //Positions after the class dimension
+        let rest = a.shape().iter().skip(2).product::<usize>();
+
+        let mut targets = target.iter_units();
+        let mut losses = Vec::<T>::with_capacity(target_shape.elements());
+
+        let mut batch_intervals = iter::repeat(Interval::all()).take(a.shape().len()).collect::<Vec<_>>();
+        let mut class_intervals = iter::repeat(Interval::all()).take(a.shape().len()).collect::<Vec<_>>();
+
+        for batch in 0..batches {
+            *batch_intervals.get_mut(0).unwrap() = Interval::only(batch);
+            let sample = a.slice(&batch_intervals);
+
+            //(classes, rest)
+            let scores = (0..classes).map(|class| {
+                *class_intervals.get_mut(1).unwrap() = Interval::only(class);
+                sample.slice(&class_intervals).iter_units().collect::<Vec<T>>()
+            }).collect::<Vec<_>>();
+
+            for r in 0..rest {
+                let class_scores = scores.iter().map(|s| *s.get(r).unwrap());
+
+                let max = class_scores.clone().fold(*scores.first().unwrap().get(r).unwrap(), |m, x| if x > m { x } else { m });
+                let log_sum_exp = max + class_scores.clone().map(|x| (x - max).exp()).sum::<T>().ln();
+
+                let target_class = unit_to_index(targets.next().unwrap())?;
+                if target_class >= classes {
+                    return Err(EngineError::IndexOutOfRange(target_class, classes));
+                }
+
+                let target_log_prob = *scores.get(target_class).unwrap().get(r).unwrap() - log_sum_exp;
+                let sum_log_probs = class_scores.map(|x| x - log_sum_exp).sum::<T>();
+
+                losses.push(target_log_prob.scale_double(label_smoothing - 1.0) - sum_log_probs.scale_double(label_smoothing / classes as f64));
+            }
+        }
+
+        Ok(reduce_loss::<T, E>(losses.into_iter(), target_shape, reduction))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{engine_impl::tensor::array::Array, helper::shape};
+    use std::f64::consts::LN_2;
+
+    use crate::engine_impl::tensor::array::Array;
 
     use super::*;
 
+    fn assert_close(res: Box<dyn EngineTensor<Unit = f64>>, expected: &[f64]) {
+        assert_eq!(res.shape().elements(), expected.len());
+
+        for (res_element, expected_element) in res.iter_units().zip(expected.iter()) {
+            assert!((res_element - expected_element).abs() < 1e-12, "{res_element} != {expected_element}");
+        }
+    }
+
     #[test]
     pub fn conv() {
         let a = Array::from_iter((1..=65536).map(|x| (x as f32) / 65536.0).cycle().take(4 * 3 * 256 * 256), shape![4, 3, 256, 256]);
         let kernel = Array::from_iter((1..=9).map(|x| x as f32).cycle().take(1 * 3 * 3 * 3), shape![1, 3, 3, 3]);
 
-        let res =  Basic::conv2d::<Array<f32>>(a.generic().as_ref(), kernel.generic().as_ref(), 2, 1).unwrap();
+        let res =  Basic::conv2d::<Array<f32>>(a.generic().as_ref(), kernel.generic().as_ref(), None, ConvParams::new([1, 1], [2, 2], [1, 1], 1)).unwrap();
 
-        println!("{:?}", res.shape());
-        //println!("{:?}", res.iter_unit().collect::<Vec<f32>>());
+        assert_eq!(*res.shape(), shape![4, 1, 258, 258]);
+    }
+
+    #[test]
+    pub fn conv_variants() {
+        //Expected values from a direct cross-correlation
+        let a = Array::from_iter((1..=10).map(|x| x as f32), shape![1, 2, 5]);
+        let kernel = Array::from_slice(&[1., 0., -1., 2., 1., 0.], shape![2, 1, 3]);
+        let bias = Array::from_slice(&[0.5, -1.], shape![2]);
+
+        //Depthwise with dilation
+        let res = Basic::conv1d::<Array<f32>>(&a, &kernel, Some(&bias), ConvParams::new([1], [1], [2], 2)).unwrap();
+        assert!(res == Array::from_slice(&[-3.5, -3.5, 2.5, 6., 19., 22.], shape![1, 2, 3]).generic());
+
+        let a = Array::from_iter((1..=16).map(|x| x as f32), shape![1, 1, 4, 4]);
+        let kernel = Array::from_slice(&[1., 2., 3., 4.], shape![1, 1, 2, 2]);
+
+        //Per axis stride and padding
+        let res = Basic::conv2d::<Array<f32>>(&a, &kernel, None, ConvParams::new([2, 1], [0, 1], [1, 1], 1)).unwrap();
+        assert!(res == Array::from_slice(&[22., 44., 54., 64., 28., 70., 124., 134., 144., 60.], shape![1, 1, 2, 5]).generic());
+
+        let a = Array::from_iter((1..=8).map(|x| x as f32), shape![1, 1, 2, 2, 2]);
+        let kernel = Array::from_iter(iter::repeat(1.).take(8), shape![1, 1, 2, 2, 2]);
+
+        let res = Basic::conv3d::<Array<f32>>(&a, &kernel, None, ConvParams::default()).unwrap();
+        assert!(res == Array::from_slice(&[36.], shape![1, 1, 1, 1, 1]).generic());
+
+        assert!(matches!(Basic::conv1d::<Array<f32>>(&Array::from_iter((1..=6).map(|x| x as f32), shape![1, 3, 2]), &Array::from_slice(&[1.], shape![1, 1, 1]), None, ConvParams::new([1], [0], [1], 2)), Err(EngineError::GroupsMismatch(2, 3))));
+
+        //Used to underflow or divide by zero
+        let a = Array::from_iter((1..=4).map(|x| x as f32), shape![1, 1, 4]);
+
+        assert!(matches!(Basic::conv1d::<Array<f32>>(&a, &Array::from_slice(&[], shape![1, 1, 0]), None, ConvParams::new([1], [0], [1], 1)), Err(EngineError::ZeroWindow(0, 1, 1))));
+        assert!(matches!(Basic::conv1d::<Array<f32>>(&a, &Array::from_slice(&[1.], shape![1, 1, 1]), None, ConvParams::new([0], [0], [1], 1)), Err(EngineError::ZeroWindow(1, 0, 1))));
+        assert!(matches!(Basic::conv1d::<Array<f32>>(&a, &Array::from_slice(&[1.], shape![1, 1, 1]), None, ConvParams::new([1], [0], [0], 1)), Err(EngineError::ZeroWindow(1, 1, 0))));
+    }
+
+    #[test]
+    pub fn conv_transpose() {
+        //Reference values from the gather form of torch.nn.functional.conv_transpose2d as given in the PyTorch docs
+        //Same inputs as torch.arange(n) * 7 % 11 - 5 and torch.arange(m) * 3 % 11 - 4 reshaped
+        let input = |shape: Shape| Array::from_iter((0..shape.elements()).map(|i| ((i * 7) % 11) as f64 - 5.), shape);
+        let weight = |shape: Shape| Array::from_iter((0..shape.elements()).map(|i| ((i * 3) % 11) as f64 - 4.), shape);
+
+        //stride=2, padding=1, output_padding=1
+        let a = input(shape![1, 2, 3, 3]);
+        let kernel = weight(shape![2, 3, 3, 3]);
+        let bias = Array::from_slice(&[1., 0., -1.], shape![3]);
+
+        let res = Basic::conv_transpose2d::<Array<f64>>(&a, &kernel, Some(&bias), ConvParams::new([2, 2], [1, 1], [1, 1], 1), [1, 1]).unwrap();
+        assert!(res == Array::from_slice(&[19., 25., -6., -3., 2., -19., -31., 45., 7., 11., 12., 19., -12., 18., -4., -32., 15., 21., 28., 20., 0., -36., -6., 33., -10., 11., -2., -39., 17., 17., 24., 7., 4., -33., -27., 17., 8., -36., -2., -1., -34., 0., 26., -63., 6., 29., -47., 26., 22., 14., -10., 27., 24., -25., 21., -29., 1., 41., -8., -13., 14., 9., -18., 22., 16., -28., 4., 5., -12., 28., 16., -28., 19., 24., -9., 1., 7., -14., -38., 38., 5., -74., 15., 22., -21., 10., -5., -35., 11., 17., 14., -12., 2., -25., -10., 29., -17., 7., -1., -38., 15., 15., 17., -30., 5., 12., -29., 15.], shape![1, 3, 6, 6]).generic());
+
+        //dilation=2
+        let a = input(shape![1, 1, 3, 3]);
+        let kernel = weight(shape![1, 2, 2, 2]);
+
+        let res = Basic::conv_transpose2d::<Array<f64>>(&a, &kernel, None, ConvParams::new([1, 1], [0, 0], [2, 2], 1), [0, 0]).unwrap();
+        assert!(res == Array::from_slice(&[20., -8., 13., -2., 2., -20., -4., 7., -1., 3., -26., 4., -17., 10., -6., 10., 2., 19., 5., -15., 8., 0., 12., 0., -20., 15., -6., 6., 0., 0., -15., -3., 9., 0., 0., -27., 6., -24., 12., -12., 15., 3., 21., 6., -18., 12., 0., 12., 0., -24.], shape![1, 2, 5, 5]).generic());
+
+        //groups=2, stride=(2, 1), padding=(1, 0), output_padding=(1, 0), dilation=(1, 2)
+        let a = input(shape![2, 4, 2, 3]);
+        let kernel = weight(shape![4, 1, 3, 2]);
+        let bias = Array::from_slice(&[2., -3.], shape![2]);
+
+        let res = Basic::conv_transpose2d::<Array<f64>>(&a, &kernel, Some(&bias), ConvParams::new([2, 1], [1, 0], [1, 2], 2), [1, 0]).unwrap();
+        assert!(res == Array::from_slice(&[-16., 6., -15., 12., -12., 22., -11., -14., -5., -9., 6., 6., 34., 6., -18., -1., -5., -21., 6., 22., 7., -17., 19., -13., -6., -10., -14., 3., -1., 13., 1., -23., 12., -14., -7., 1., 17., -7., -7., 7., 6., 6., -8., 30., 6., -11., -11., -22., -2., -6., 6., 6., -14., 24., 0., -9., -2., 14., -6., 10., 25., 1., -26., -10., -14., -7., -11., 67., 5., -25., 19., -5., -22., -11., 29., -11., 5., -13., -1., -20.], shape![2, 2, 4, 5]).generic());
+
+        //Transposed convolution is the adjoint of convolution with the same kernel, <conv_transpose(a), b> = <a, conv(b)>
+        let b = Array::from_iter((0..res.shape().elements()).map(|i| ((i * 5) % 13) as f64 - 6.), res.shape().clone());
+        let res = Basic::conv_transpose2d::<Array<f64>>(&a, &kernel, None, ConvParams::new([2, 1], [1, 0], [1, 2], 2), [1, 0]).unwrap();
+        let conv = Basic::conv2d::<Array<f64>>(&b, &kernel, None, ConvParams::new([2, 1], [1, 0], [1, 2], 2)).unwrap();
+
+        assert_eq!(*conv.shape(), *a.shape());
+        assert_eq!(res.iter_units().zip(b.iter_units()).map(|(x, y)| x * y).sum::<f64>(), a.iter_units().zip(conv.iter_units()).map(|(x, y)| x * y).sum::<f64>());
+
+        assert!(matches!(Basic::conv_transpose2d::<Array<f64>>(&a, &kernel, None, ConvParams::new([1, 1], [0, 0], [1, 1], 2), [1, 1]), Err(EngineError::PaddingTooLarge(1, 1))));
     }
 
     #[test]
@@ -269,4 +1109,593 @@ mod test {
 
         assert!(res == expected.generic());
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn losses() {
+        let a = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+        let b = Array::from_slice(&[1., 0., 5., 4.5], shape![2, 2]);
+
+        let res = Basic::mse_loss::<Array<f64>>(&a, &b, Reduction::Mean).unwrap();
+        assert!(res == Array::from_slice(&[2.0625], shape![1]).generic());
+
+        let res = Basic::l1_loss::<Array<f64>>(&a, &b, Reduction::Sum).unwrap();
+        assert!(res == Array::from_slice(&[4.5], shape![1]).generic());
+
+        let res = Basic::huber_loss::<Array<f64>>(&a, &b, 1.0, Reduction::None).unwrap();
+        assert!(res == Array::from_slice(&[0., 1.5, 1.5, 0.125], shape![2, 2]).generic());
+
+        //Large logits shouldn't overflow
+        let logits = Array::from_slice(&[1000., -1000., 0., 2.], shape![4]);
+        let targets = Array::from_slice(&[0., 0., 1., 1.], shape![4]);
+
+        let res = Basic::binary_cross_entropy_with_logits::<Array<f64>>(&logits, &targets, Reduction::None).unwrap();
+        for (res_element, expected_element) in res.iter_units().zip([1000.0, 0.0, LN_2, 0.1269280110429725]) {
+            assert!((res_element - expected_element).abs() < 1e-12);
+        }
+
+        let logits = Array::from_slice(&[1., 2., 3., 1000., 0., -1000.], shape![2, 3]);
+        let targets = Array::from_slice(&[2., 1.], shape![2]);
+
+        let res = Basic::cross_entropy::<Array<f64>>(&logits, &targets, 0.1, Reduction::None).unwrap();
+        for (res_element, expected_element) in res.iter_units().zip([0.5076059644443807, 1000.0]) {
+            assert!((res_element - expected_element).abs() < 1e-9, "{res_element}");
+        }
+
+        let targets = Array::from_slice(&[3., 1.], shape![2]);
+        assert!(matches!(Basic::cross_entropy::<Array<f64>>(&logits, &targets, 0.0, Reduction::Mean), Err(EngineError::IndexOutOfRange(3, 3))));
+
+        //Negative and fractional targets aren't classes
+        for target in [-1., 0.5] {
+            let targets = Array::from_slice(&[0., target], shape![2]);
+            assert!(matches!(Basic::cross_entropy::<Array<f64>>(&logits, &targets, 0.0, Reduction::Mean), Err(EngineError::InvalidIndexUnit(_))));
+        }
+    }
+
+    #[test]
+    pub fn reductions() {
+        let a = Array::from_iter((1..=24).map(|x| x as f32), shape![2, 3, 4]);
+
+        let res = Basic::sum::<Array<f32>>(&a, &[1], false).unwrap();
+        assert!(res == Array::from_slice(&[15., 18., 21., 24., 51., 54., 57., 60.], shape![2, 4]).generic());
+
+        let res = Basic::mean::<Array<f32>>(&a, &[0, 2], true).unwrap();
+        assert!(res == Array::from_slice(&[8.5, 12.5, 16.5], shape![1, 3, 1]).generic());
+
+        let res = Basic::max::<Array<f32>>(&a, &[], false).unwrap();
+        assert!(res == Array::from_slice(&[24.], shape![1]).generic());
+
+        let res = Basic::product::<Array<f32>>(&a, &[0], false).unwrap();
+        assert!(res == Array::from_iter((1..=12).map(|x| (x * (x + 12)) as f32), shape![3, 4]).generic());
+
+        let b = Array::from_slice(&[3., 1., 4., 1., 5., 9., 2., 6.], shape![2, 4]);
+
+        let res = Basic::argmax::<Array<usize>>(&b, 1, false).unwrap();
+        assert!(res == Array::from_slice(&[2, 1], shape![2]).generic());
+
+        //The indices can be used to pick the max straight back out
+        let picked = Basic::gather::<Array<f32>>(&b, 1, Basic::argmax::<Array<usize>>(&b, 1, true).unwrap().as_ref()).unwrap();
+        assert!(picked == Array::from_slice(&[4., 9.], shape![2, 1]).generic());
+
+        let res = Basic::argmin::<Array<usize>>(&b, 0, true).unwrap();
+        assert!(res == Array::from_slice(&[0, 0, 1, 0], shape![1, 4]).generic());
+
+        assert!(matches!(Basic::min::<Array<f32>>(&b, &[2], false), Err(EngineError::IndexOutOfRange(2, 2))));
+
+        let empty = Array::<f32>::from_slice(&[], shape![2, 0]);
+
+        assert!(matches!(Basic::max::<Array<f32>>(&empty, &[1], false), Err(EngineError::EmptyReduction(_))));
+        assert!(matches!(Basic::argmin::<Array<usize>>(&empty, 1, false), Err(EngineError::EmptyReduction(_))));
+        assert!(Basic::sum::<Array<f32>>(&empty, &[1], false).unwrap() == Array::from_slice(&[0., 0.], shape![2]).generic());
+    }
+
+    #[test]
+    pub fn broadcasting() {
+        let a = Array::from_iter((1..=6).map(|x| x as f32), shape![2, 3]);
+        let row = Array::from_slice(&[10., 20., 30.], shape![3]);
+        let column = Array::from_slice(&[1., 2.], shape![2, 1]);
+        let scalar = Array::from_slice(&[2.], shape![1]);
+
+        let res = Basic::add::<Array<f32>>(&a, &row).unwrap();
+        assert!(res == Array::from_slice(&[11., 22., 33., 14., 25., 36.], shape![2, 3]).generic());
+
+        let res = Basic::mul::<Array<f32>>(&column, &row).unwrap();
+        assert!(res == Array::from_slice(&[10., 20., 30., 20., 40., 60.], shape![2, 3]).generic());
+
+        let res = Basic::div::<Array<f32>>(&a, &scalar).unwrap();
+        assert!(res == Array::from_slice(&[0.5, 1., 1.5, 2., 2.5, 3.], shape![2, 3]).generic());
+
+        let res = Basic::sub::<Array<f32>>(&scalar, &a).unwrap();
+        assert!(res == Array::from_slice(&[1., 0., -1., -2., -3., -4.], shape![2, 3]).generic());
+
+        assert!(matches!(Basic::add::<Array<f32>>(&a, column.reshape(&shape![2]).as_ref()), Err(EngineError::ShapeMismatch(_, _))));
+    }
+
+    #[test]
+    pub fn scalar_ops() {
+        let a = Array::from_slice(&[1., 2., 4.], shape![3]);
+
+        assert!(Basic::add_scalar::<Array<f32>>(1., &a).unwrap() == Array::from_slice(&[2., 3., 5.], shape![3]).generic());
+        assert!(Basic::sub_scalar_lh::<Array<f32>>(1., &a).unwrap() == Array::from_slice(&[0., -1., -3.], shape![3]).generic());
+        assert!(Basic::sub_scalar_rh::<Array<f32>>(&a, 1.).unwrap() == Array::from_slice(&[0., 1., 3.], shape![3]).generic());
+        assert!(Basic::mul_scalar::<Array<f32>>(2., &a).unwrap() == Array::from_slice(&[2., 4., 8.], shape![3]).generic());
+        assert!(Basic::div_scalar_lh::<Array<f32>>(4., &a).unwrap() == Array::from_slice(&[4., 2., 1.], shape![3]).generic());
+        assert!(Basic::div_scalar_rh::<Array<f32>>(&a, 4.).unwrap() == Array::from_slice(&[0.25, 0.5, 1.], shape![3]).generic());
+        assert!(Basic::pow_scalar::<Array<f32>>(&a, 2.).unwrap() == Array::from_slice(&[1., 4., 16.], shape![3]).generic());
+    }
+
+    #[test]
+    pub fn activations() {
+        let a = Array::from_slice(&[-3., -1., 0., 0.5, 2.], shape![5]);
+
+        //Expected values from the defining formulas evaluated with Python's math module
+        assert_close(Basic::gelu::<Array<f64>>(&a).unwrap(), &[-0.00404969409489031, -0.15865525393145707, 0.0, 0.34573123063700656, 1.9544997361036416]);
+        assert_close(Basic::gelu_tanh::<Array<f64>>(&a).unwrap(), &[-0.0036373920817729943, -0.15880800939172324, 0.0, 0.34571400982514394, 1.954597694087775]);
+        assert_close(Basic::silu::<Array<f64>>(&a).unwrap(), &[-0.14227761953270035, -0.2689414213699951, 0.0, 0.3112296656009273, 1.7615941559557646]);
+        assert_close(Basic::mish::<Array<f64>>(&a).unwrap(), &[-0.1456474612756246, -0.30340146137410895, 0.0, 0.3752452113048951, 1.9439589595339946]);
+        assert_close(Basic::elu::<Array<f64>>(&a, 1.).unwrap(), &[-0.950212931632136, -0.6321205588285577, 0.0, 0.5, 2.0]);
+        assert_close(Basic::selu::<Array<f64>>(&a).unwrap(), &[-1.6705687287671118, -1.1113307378125625, 0.0, 0.5253504936777402, 2.101401974710961]);
+        assert_close(Basic::softplus::<Array<f64>>(&a, 1., 20.).unwrap(), &[0.04858735157374206, 0.31326168751822286, LN_2, 0.9740769841801067, 2.1269280110429727]);
+        assert_close(Basic::hardswish::<Array<f64>>(&a).unwrap(), &[0., -0.3333333333333333, 0.0, 0.2916666666666667, 1.6666666666666667]);
+        assert_close(Basic::hardtanh::<Array<f64>>(&a, -1., 1.).unwrap(), &[-1., -1., 0., 0.5, 1.]);
+
+        //Large magnitudes used to overflow to NaN
+        let a = Array::from_slice(&[-1000., 1000.], shape![2]);
+
+        assert!(Basic::sigmoid::<Array<f32>>(&a).unwrap() == Array::from_slice(&[0., 1.], shape![2]).generic());
+        assert!(Basic::softplus::<Array<f32>>(&a, 1., f64::INFINITY).unwrap() == Array::from_slice(&[0., 1000.], shape![2]).generic());
+
+        let a = Array::from_slice(&[-1., 2., -3., 4.], shape![1, 2, 2]);
+
+        assert!(Basic::prelu::<Array<f32>>(&a, &Array::from_slice(&[0.25], shape![1])).unwrap() == Array::from_slice(&[-0.25, 2., -0.75, 4.], shape![1, 2, 2]).generic());
+        assert!(Basic::prelu::<Array<f32>>(&a, &Array::from_slice(&[0.5, 0.], shape![2])).unwrap() == Array::from_slice(&[-0.5, 2., 0., 4.], shape![1, 2, 2]).generic());
+        assert!(Basic::prelu::<Array<f32>>(&a, &Array::from_slice(&[0.5, 0., 1.], shape![3])).is_err());
+    }
+
+    #[test]
+    pub fn softmax() {
+        let a = Array::from_slice(&[1., 2., 3., -1., 0., 4.], shape![2, 3]);
+
+        let rows = [0.09003057317038046, 0.24472847105479764, 0.6652409557748218, 0.006573263185309083, 0.0178679818703045, 0.9755587549443865];
+        let columns = [0.8807970779778823, 0.8807970779778823, 0.2689414213699951, 0.11920292202211755, 0.11920292202211755, 0.7310585786300049];
+
+        let res = Basic::softmax::<Array<f64>>(&a, 1).unwrap();
+        assert_eq!(*res.shape(), shape![2, 3]);
+        assert_close(res, &rows);
+
+        let res = Basic::softmax::<Array<f64>>(&a, 0).unwrap();
+        assert_eq!(*res.shape(), shape![2, 3]);
+        assert_close(res, &columns);
+
+        let res = Basic::softmax::<Array<f64>>(a.reshape(&shape![1, 2, 3]).as_ref(), 1).unwrap();
+        assert_eq!(*res.shape(), shape![1, 2, 3]);
+        assert_close(res, &columns);
+
+        let res = Basic::log_softmax::<Array<f64>>(&a, 1).unwrap();
+        assert_eq!(*res.shape(), shape![2, 3]);
+        assert_close(res, &[-2.4076059644443806, -1.4076059644443804, -0.4076059644443804, -5.024744890138822, -4.024744890138822, -0.02474489013882259]);
+
+        //Would overflow without subtracting the max
+        let a = Array::from_slice(&[1000., 1001.], shape![2]);
+
+        let res = Basic::softmax::<Array<f64>>(&a, 0).unwrap();
+        assert_eq!(*res.shape(), shape![2]);
+        assert_close(res, &[0.2689414213699951, 0.7310585786300049]);
+
+        let res = Basic::log_softmax::<Array<f64>>(&a, 0).unwrap();
+        assert_eq!(*res.shape(), shape![2]);
+        assert_close(res, &[-1.3132616875182228, -0.31326168751822286]);
+
+        assert!(Basic::softmax::<Array<f64>>(&a, 1).is_err());
+
+        //Empty axes and empty leading dims give back empty tensors
+        let a = Array::from_slice(&[], shape![2, 0]);
+
+        assert_eq!(*Basic::softmax::<Array<f64>>(&a, 1).unwrap().shape(), shape![2, 0]);
+        assert_eq!(*Basic::log_softmax::<Array<f64>>(&a, 0).unwrap().shape(), shape![2, 0]);
+
+        let a = Array::from_slice(&[], shape![0, 3]);
+
+        assert_eq!(Basic::softmax::<Array<f64>>(&a, 1).unwrap().iter_units().count(), 0);
+    }
+
+    #[test]
+    pub fn join_and_split() {
+        let a = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+        let b = Array::from_slice(&[5., 6.], shape![2, 1]);
+
+        let res = Basic::concat::<Array<f32>>(&[&a, &b], 1).unwrap();
+        assert!(res == Array::from_slice(&[1., 2., 5., 3., 4., 6.], shape![2, 3]).generic());
+
+        assert!(matches!(Basic::concat::<Array<f32>>(&[&a, &b], 0), Err(EngineError::ShapeMismatch(_, _))));
+        assert!(matches!(Basic::concat::<Array<f32>>(&[], 0), Err(EngineError::NoTensors())));
+
+        let res = Basic::stack::<Array<f32>>(&[&a, &a], 0).unwrap();
+        assert!(res == Array::from_slice(&[1., 2., 3., 4., 1., 2., 3., 4.], shape![2, 2, 2]).generic());
+
+        let res = Basic::stack::<Array<f32>>(&[&a, &a], 2).unwrap();
+        assert!(res == Array::from_slice(&[1., 1., 2., 2., 3., 3., 4., 4.], shape![2, 2, 2]).generic());
+
+        let c = Array::from_iter((0..10).map(|x| x as f32), shape![2, 5]);
+
+        let parts = Basic::split(&c, &[1, 4], 1).unwrap();
+        assert!(parts[0] == Array::from_slice(&[0., 5.], shape![2, 1]).generic());
+        assert!(parts[1] == Array::from_slice(&[1., 2., 3., 4., 6., 7., 8., 9.], shape![2, 4]).generic());
+        assert!(Basic::split(&c, &[1, 3], 1).is_err());
+
+        let parts = Basic::chunk(&c, 2, 1).unwrap();
+        assert_eq!(parts.iter().map(|p| p.shape().get(1).unwrap()).collect::<Vec<_>>(), [3, 2]);
+        assert!(Basic::concat::<Array<f32>>(&parts.iter().map(|p| p.as_ref()).collect::<Vec<_>>(), 1).unwrap() == c.clone());
+
+        //Chunks of ceil(4 / 3) only need 2 chunks
+        assert_eq!(Basic::chunk(c.slice(&[Interval::all(), Interval::start_to(4)]).as_ref(), 3, 1).unwrap().len(), 2);
+        assert!(matches!(Basic::chunk(&c, 0, 1), Err(EngineError::InvalidChunks(0))));
+
+        let empty = Array::<f32>::from_slice(&[], shape![2, 0]);
+        let parts = Basic::chunk(&empty, 3, 1).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(*parts[0].shape(), shape![2, 0]);
+    }
+
+    #[test]
+    pub fn reorder() {
+        let a = Array::from_slice(&[1., 2., 3., 4., 5., 6.], shape![2, 3]);
+        let weight = Array::from_slice(&[10., 20., 30., 11., 21., 31.], shape![2, 3]);
+
+        //a @ weight^T without copying weight first
+        let res = Basic::matmul::<Array<f32>>(&a, Basic::transpose(&weight, 0, 1).unwrap().as_ref()).unwrap();
+        assert!(res == Array::from_slice(&[140., 146., 320., 335.], shape![2, 2]).generic());
+
+        assert_eq!(*Basic::permute(&Array::from_iter((0..24).map(|x| x as f32), shape![2, 3, 4]), &[1, 2, 0]).unwrap().shape(), shape![3, 4, 2]);
+
+        assert!(matches!(Basic::permute(&a, &[0, 0]), Err(EngineError::InvalidPermutation(_, 2))));
+        assert!(matches!(Basic::permute(&a, &[0]), Err(EngineError::InvalidPermutation(_, 2))));
+        assert!(matches!(Basic::transpose(&a, 0, 2), Err(EngineError::IndexOutOfRange(2, 2))));
+
+        let b = Array::from_iter((0..24).map(|x| x as f32), shape![2, 3, 4]);
+
+        assert_eq!(*Basic::reshape(&b, &shape![6, 4]).unwrap().shape(), shape![6, 4]);
+        assert_eq!(*Basic::flatten(&b, 1, 2).unwrap().shape(), shape![2, 12]);
+        assert_eq!(*Basic::unflatten(&b, 2, &[2, 2]).unwrap().shape(), shape![2, 3, 2, 2]);
+
+        //Flattening a permuted view has to follow the permuted order
+        let permuted = Basic::permute(&b, &[0, 2, 1]).unwrap();
+        assert!(Basic::flatten(permuted.as_ref(), 1, 2).unwrap().iter_units().take(4).eq([0., 4., 8., 1.]));
+
+        assert!(Basic::reshape(&b, &shape![5, 5]).is_err());
+        assert!(Basic::flatten(&b, 1, 3).is_err());
+        assert!(Basic::unflatten(&b, 2, &[3, 2]).is_err());
+
+        assert!(Basic::narrow(&b, 1, 1, 2).unwrap() == Array::from_iter([4., 5., 6., 7., 8., 9., 10., 11., 16., 17., 18., 19., 20., 21., 22., 23.].into_iter(), shape![2, 2, 4]).generic());
+        assert!(matches!(Basic::narrow(&b, 1, 2, 2), Err(EngineError::IndexOutOfRange(4, 3))));
+    }
+
+    #[test]
+    pub fn attention() {
+        let q = Array::from_slice(&[1., 0., 0., 1.], shape![2, 2]);
+        let k = Array::from_slice(&[1., 0., 0., 1.], shape![2, 2]);
+        let v = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+
+        //Hand computed, a matching query and key score 1 / sqrt(2) against 0 so get sigmoid(1 / sqrt(2)) of the weight
+        let w = 0.6697615493266569;
+
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&q, &k, &v, None, None).unwrap();
+        assert_close(res, &[w + (1. - w) * 3., w * 2. + (1. - w) * 4., (1. - w) + w * 3., (1. - w) * 2. + w * 4.]);
+
+        //The first query can only see the first key
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&q, &k, &v, None, Some(0)).unwrap();
+        assert_close(res, &[1., 2., (1. - w) + w * 3., (1. - w) * 2. + w * 4.]);
+
+        //Aligned to the top left like PyTorch, a single query only sees the first key unless offset
+        let first_q = Array::from_slice(&[1., 0.], shape![1, 2]);
+
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&first_q, &k, &v, None, Some(0)).unwrap();
+        assert_close(res, &[1., 2.]);
+
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&first_q, &k, &v, None, Some(1)).unwrap();
+        assert_close(res, &[w + (1. - w) * 3., w * 2. + (1. - w) * 4.]);
+
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&q, &k, &v, Some(&Array::from_slice(&[1., 0.], shape![2])), None).unwrap();
+        assert_close(res, &[1., 2., 1., 2.]);
+
+        let res = Basic::scaled_dot_product_attention::<Array<f64>>(&q, &k, &v, Some(&Array::from_slice(&[0., 0.], shape![2])), None).unwrap();
+        assert_close(res, &[0., 0., 0., 0.]);
+
+        assert!(Basic::scaled_dot_product_attention::<Array<f64>>(&q, &k, &Array::from_slice(&[1., 2., 3.], shape![3, 1]), None, None).is_err());
+    }
+
+    #[test]
+    pub fn indexing() {
+        let a = Array::from_iter((0..12).map(|x| x as f32), shape![3, 4]);
+        let indices = Array::from_slice(&[2, 0], shape![2]);
+
+        assert!(Basic::index_select::<Array<f32>>(&a, 0, &indices).unwrap() == Array::from_slice(&[8., 9., 10., 11., 0., 1., 2., 3.], shape![2, 4]).generic());
+        assert!(Basic::index_select::<Array<f32>>(&a, 1, &indices).unwrap() == Array::from_slice(&[2., 0., 6., 4., 10., 8.], shape![3, 2]).generic());
+        assert!(matches!(Basic::index_select::<Array<f32>>(&a, 0, &Array::from_slice(&[3], shape![1])), Err(EngineError::IndexOutOfRange(3, 3))));
+
+        //Examples from the PyTorch documentation
+        let t = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+        let index = Array::from_slice(&[0, 0, 1, 0], shape![2, 2]);
+
+        assert!(Basic::gather::<Array<f32>>(&t, 1, &index).unwrap() == Array::from_slice(&[1., 1., 4., 3.], shape![2, 2]).generic());
+        assert!(matches!(Basic::gather::<Array<f32>>(&t, 1, &Array::from_slice(&[2], shape![1, 1])), Err(EngineError::IndexOutOfRange(2, 2))));
+
+        let zeros = Array::from_iter(iter::repeat(0.).take(15), shape![3, 5]);
+        let src = Array::from_iter((1..=10).map(|x| x as f32), shape![2, 5]);
+        let index = Array::from_slice(&[0, 1, 2, 0], shape![1, 4]);
+
+        let res = Basic::scatter::<Array<f32>>(&zeros, 0, &index, &src).unwrap();
+        assert!(res == Array::from_slice(&[1., 0., 0., 4., 0., 0., 2., 0., 0., 0., 0., 0., 3., 0., 0.], shape![3, 5]).generic());
+
+        let ones = Array::from_iter(iter::repeat(1.).take(10), shape![2, 5]);
+        let index = Array::from_slice(&[0, 1, 2, 0, 0], shape![1, 5]);
+
+        let res = Basic::scatter_add::<Array<f32>>(&zeros, 0, &index, &ones).unwrap();
+        assert!(res == Array::from_slice(&[1., 0., 0., 1., 1., 0., 1., 0., 0., 0., 0., 0., 1., 0., 0.], shape![3, 5]).generic());
+
+        let index = Array::from_slice(&[0, 1, 2, 0, 0, 0, 1, 2, 2, 2], shape![2, 5]);
+
+        let res = Basic::scatter_add::<Array<f32>>(&zeros, 0, &index, &ones).unwrap();
+        assert!(res == Array::from_slice(&[2., 0., 0., 1., 1., 0., 2., 0., 0., 0., 0., 0., 2., 1., 1.], shape![3, 5]).generic());
+
+        assert!(matches!(Basic::scatter::<Array<f32>>(&zeros, 0, &Array::from_slice(&[0, 1, 2, 0, 0, 0], shape![1, 6]), &src), Err(EngineError::DimensionMismatch(6, 5))));
+    }
+
+    #[test]
+    pub fn comparison_and_selection() {
+        let a = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+        let b = Array::from_slice(&[2., 2.], shape![2]);
+
+        assert!(Basic::eq::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[0., 1., 0., 0.], shape![2, 2]).generic());
+        assert!(Basic::ne::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[1., 0., 1., 1.], shape![2, 2]).generic());
+        assert!(Basic::lt::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[1., 0., 0., 0.], shape![2, 2]).generic());
+        assert!(Basic::le::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[1., 1., 0., 0.], shape![2, 2]).generic());
+        assert!(Basic::gt::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[0., 0., 1., 1.], shape![2, 2]).generic());
+        assert!(Basic::ge::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[0., 1., 1., 1.], shape![2, 2]).generic());
+        assert!(Basic::maximum::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[2., 2., 3., 4.], shape![2, 2]).generic());
+        assert!(Basic::minimum::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[1., 2., 2., 2.], shape![2, 2]).generic());
+
+        let cond = Array::from_slice(&[1., 0.], shape![2, 1]);
+        let other = Array::from_slice(&[0.], shape![1]);
+
+        assert!(Basic::where_::<Array<f32>>(&cond, &a, &other).unwrap() == Array::from_slice(&[1., 2., 0., 0.], shape![2, 2]).generic());
+        assert!(Basic::where_::<Array<f32>>(&cond, &a, &Array::from_slice(&[0., 0., 0.], shape![3])).is_err());
+
+        assert!(Basic::clamp::<Array<f32>>(&a, Some(2.), Some(3.)).unwrap() == Array::from_slice(&[2., 2., 3., 3.], shape![2, 2]).generic());
+        assert!(Basic::clamp::<Array<f32>>(&a, None, Some(3.)).unwrap() == Array::from_slice(&[1., 2., 3., 3.], shape![2, 2]).generic());
+
+        let mask = Array::from_slice(&[0., 1.], shape![2]);
+
+        assert!(Basic::masked_fill::<Array<f32>>(&a, &mask, -1.).unwrap() == Array::from_slice(&[1., -1., 3., -1.], shape![2, 2]).generic());
+        //The mask may only broadcast to a, not the other way round
+        assert!(Basic::masked_fill::<Array<f32>>(&b, &a, -1.).is_err());
+    }
+
+    #[test]
+    pub fn embedding() {
+        let weight = Array::from_slice(&[3., 4., 1., 0., 0., 2.], shape![3, 2]);
+        let ids = Array::from_slice(&[2i32, 0, 1, 1], shape![2, 2]);
+
+        assert!(Basic::embedding::<Array<f64>, i32>(&weight, &ids, None, None).unwrap() == Array::from_slice(&[0., 2., 3., 4., 1., 0., 1., 0.], shape![2, 2, 2]).generic());
+        //Like PyTorch padding_idx only matters to gradients so the forward is unchanged
+        assert!(Basic::embedding::<Array<f64>, i32>(&weight, &ids, Some(1), None).unwrap() == Array::from_slice(&[0., 2., 3., 4., 1., 0., 1., 0.], shape![2, 2, 2]).generic());
+        assert!(matches!(Basic::embedding::<Array<f64>, i32>(&weight, &ids, Some(3), None), Err(EngineError::IndexOutOfRange(3, 3))));
+
+        //Only the first row has a norm (5) over the max norm
+        let res = Basic::embedding::<Array<f64>, i32>(&weight, &ids, None, Some(2.5)).unwrap();
+        assert!(res.iter_units().zip([0., 2., 1.5, 2., 1., 0., 1., 0.]).all(|(x, y)| (x - y).abs() < 1e-7));
+
+        assert!(matches!(Basic::embedding::<Array<f64>, usize>(&weight, &Array::from_slice(&[3], shape![1]), None, None), Err(EngineError::IndexOutOfRange(3, 3))));
+
+        //Casting would have wrapped or truncated these to a valid looking row
+        assert!(matches!(Basic::embedding::<Array<f64>, i32>(&weight, &Array::from_slice(&[-1], shape![1]), None, None), Err(EngineError::InvalidIndexUnit(_))));
+        assert!(matches!(Basic::embedding::<Array<f64>, f64>(&weight, &Array::from_slice(&[-1.], shape![1]), None, None), Err(EngineError::InvalidIndexUnit(_))));
+        assert!(matches!(Basic::embedding::<Array<f64>, f64>(&weight, &Array::from_slice(&[1.9], shape![1]), None, None), Err(EngineError::InvalidIndexUnit(_))));
+        assert!(Basic::embedding::<Array<f64>, f64>(&weight, &Array::from_slice(&[2.], shape![1]), None, None).unwrap() == Array::from_slice(&[0., 2.], shape![1, 2]).generic());
+
+        let bags = Array::from_slice(&[0, 1, 2, 1, 1, 1], shape![2, 3]);
+
+        assert!(Basic::embedding_bag::<Array<f64>, usize>(&weight, &bags, EmbeddingBagMode::Sum, None).unwrap() == Array::from_slice(&[4., 6., 3., 0.], shape![2, 2]).generic());
+        //The second bag is all padding
+        assert!(Basic::embedding_bag::<Array<f64>, usize>(&weight, &bags, EmbeddingBagMode::Mean, Some(1)).unwrap() == Array::from_slice(&[1.5, 3., 0., 0.], shape![2, 2]).generic());
+
+        let classes = Array::from_slice(&[2u8, 0], shape![2]);
+
+        assert!(Basic::one_hot::<Array<f32>, u8>(&classes, 3).unwrap() == Array::from_slice(&[0., 0., 1., 1., 0., 0.], shape![2, 3]).generic());
+        assert!(matches!(Basic::one_hot::<Array<f32>, u8>(&classes, 2), Err(EngineError::IndexOutOfRange(2, 2))));
+        assert!(matches!(Basic::one_hot::<Array<f32>, f32>(&Array::from_slice(&[0.5], shape![1]), 2), Err(EngineError::InvalidIndexUnit(_))));
+    }
+
+    #[test]
+    pub fn random() {
+        let shape = shape![10000];
+
+        let mean_std = |res: Box<dyn EngineTensor<Unit = f64>>| {
+            let n = res.shape().elements() as f64;
+            let mean = res.iter_units().sum::<f64>() / n;
+
+            (mean, (res.iter_units().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n).sqrt())
+        };
+
+        //Loose bounds, 10000 samples put the sample mean well within them
+        let res = Basic::rand_uniform::<Array<f64>>(&shape, -1., 3., &mut Rng::new(0)).unwrap();
+        assert!(res.iter_units().all(|x| (-1.0..3.0).contains(&x)));
+        let (mean, std) = mean_std(res);
+        assert!((mean - 1.).abs() < 0.05 && (std - 4. / 12f64.sqrt()).abs() < 0.05);
+
+        let (mean, std) = mean_std(Basic::rand_normal::<Array<f64>>(&shape, 2., 0.5, &mut Rng::new(1)).unwrap());
+        assert!((mean - 2.).abs() < 0.05 && (std - 0.5).abs() < 0.05);
+
+        let (mean, _) = mean_std(Basic::bernoulli::<Array<f64>>(&shape, 0.25, &mut Rng::new(2)).unwrap());
+        assert!((mean - 0.25).abs() < 0.05);
+        assert!(matches!(Basic::bernoulli::<Array<f64>>(&shape, -0.1, &mut Rng::new(2)), Err(EngineError::InvalidProbability(_))));
+
+        let res = Basic::randint::<Array<i32>>(&shape, 3, 6, &mut Rng::new(3)).unwrap();
+        assert!((3..6).all(|i| res.iter_units().any(|x| x == i)) && res.iter_units().all(|x| (3..6).contains(&x)));
+        assert!(matches!(Basic::randint::<Array<i32>>(&shape, 3, 3, &mut Rng::new(3)), Err(EngineError::EmptyRange(3, 3))));
+
+        //The same seed gives the same tensor
+        assert!(Basic::rand_normal::<Array<f32>>(&shape, 0., 1., &mut Rng::new(4)).unwrap() == Basic::rand_normal::<Array<f32>>(&shape, 0., 1., &mut Rng::new(4)).unwrap());
+    }
+
+    #[test]
+    pub fn dropout() {
+        let a = Array::from_iter(iter::repeat(1.).take(10000), shape![100, 100]);
+
+        let res = Basic::dropout::<Array<f64>>(&a, 0.2, true, &mut Rng::new(5)).unwrap();
+        assert!(res.iter_units().all(|x| x == 0. || x == 1.25));
+        assert!((res.iter_units().filter(|x| *x == 0.).count() as f64 / 10000. - 0.2).abs() < 0.05);
+        assert!(res == Basic::dropout::<Array<f64>>(&a, 0.2, true, &mut Rng::new(5)).unwrap());
+
+        assert!(Basic::dropout::<Array<f64>>(&a, 0.2, false, &mut Rng::new(5)).unwrap().iter_units().all(|x| x == 1.));
+        assert!(Basic::dropout::<Array<f64>>(&a, 1., true, &mut Rng::new(5)).unwrap().iter_units().all(|x| x == 0.));
+        assert!(Basic::dropout::<Array<f64>>(&a, 0., true, &mut Rng::new(5)).unwrap().iter_units().all(|x| x == 1.));
+        assert!(matches!(Basic::dropout::<Array<f64>>(&Array::from_slice(&[1.], shape![1]), 1.5, true, &mut Rng::new(5)), Err(EngineError::InvalidProbability(_))));
+    }
+
+    #[test]
+    pub fn elementwise_math() {
+        let a = Array::from_slice(&[1., 4., 16.], shape![3]);
+
+        assert!(Basic::sqrt::<Array<f32>>(&a).unwrap() == Array::from_slice(&[1., 2., 4.], shape![3]).generic());
+        assert!(Basic::rsqrt::<Array<f32>>(&a).unwrap() == Array::from_slice(&[1., 0.5, 0.25], shape![3]).generic());
+        assert!(Basic::reciprocal::<Array<f32>>(&a).unwrap() == Array::from_slice(&[1., 0.25, 0.0625], shape![3]).generic());
+
+        let a = Array::from_slice(&[0., 1e-10, 1.], shape![3]);
+
+        //Naive exp(x) - 1 and ln(1 + x) lose 1e-10 entirely
+        assert!(Basic::expm1::<Array<f64>>(&a).unwrap().iter_units().zip([0., 1e-10, 1f64.exp() - 1.]).all(|(x, y)| (x - y).abs() < 1e-15));
+        assert!(Basic::log1p::<Array<f64>>(&a).unwrap().iter_units().zip([0., 1e-10, 2f64.ln()]).all(|(x, y)| (x - y).abs() < 1e-15));
+        assert!(Basic::log::<Array<f64>>(Basic::exp::<Array<f64>>(&a).unwrap().as_ref()).unwrap().iter_units().zip(a.iter_units()).all(|(x, y)| (x - y).abs() < 1e-15));
+
+        let a = Array::from_slice(&[0., 0.5, -1.], shape![3]);
+
+        assert!(Basic::tanh::<Array<f64>>(Basic::atanh::<Array<f64>>(a.slice(&[Interval::between(0, 2)]).as_ref()).unwrap().as_ref()).unwrap().iter_units().zip([0., 0.5]).all(|(x, y)| (x - y).abs() < 1e-15));
+        assert!(Basic::sin::<Array<f64>>(Basic::asin::<Array<f64>>(&a).unwrap().as_ref()).unwrap().iter_units().zip(a.iter_units()).all(|(x, y)| (x - y).abs() < 1e-15));
+
+        let a = Array::from_slice(&[-1.5, -0.5, 0., 0.5, 1.5, 2.5], shape![6]);
+
+        assert!(Basic::floor::<Array<f32>>(&a).unwrap() == Array::from_slice(&[-2., -1., 0., 0., 1., 2.], shape![6]).generic());
+        assert!(Basic::ceil::<Array<f32>>(&a).unwrap() == Array::from_slice(&[-1., -0., 0., 1., 2., 3.], shape![6]).generic());
+        assert!(Basic::round::<Array<f32>>(&a).unwrap() == Array::from_slice(&[-2., -0., 0., 0., 2., 2.], shape![6]).generic());
+        assert!(Basic::sign::<Array<f32>>(&a).unwrap() == Array::from_slice(&[-1., -1., 0., 1., 1., 1.], shape![6]).generic());
+        assert!(Basic::sign::<Array<u8>>(&Array::from_slice(&[0, 3], shape![2])).unwrap() == Array::from_slice(&[0, 1], shape![2]).generic());
+
+        let a = Array::from_slice(&[1., 2., 3., 4.], shape![2, 2]);
+        let b = Array::from_slice(&[2., 0.5], shape![2]);
+
+        assert!(Basic::pow::<Array<f32>>(&a, &b).unwrap() == Array::from_slice(&[1., 2f32.sqrt(), 9., 2.], shape![2, 2]).generic());
+    }
+
+    #[test]
+    pub fn batch_norm() {
+        let a = Array::from_slice(&[1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12.5], shape![2, 2, 3]);
+        let weight = Array::from_slice(&[1., 2.], shape![2]);
+        let bias = Array::from_slice(&[0., 0.5], shape![2]);
+        let running_mean = Array::from_slice(&[0.5, -1.], shape![2]);
+        let running_var = Array::from_slice(&[2., 0.5], shape![2]);
+
+        //PyTorch generated
+        let res = Basic::batch_norm_no_running::<Array<f64>>(&a, &weight, &bias, 1e-5).unwrap();
+        assert_close(res, &[-1.2865343763567105, -0.964900782267533, -0.6432671881783553, -2.0362727895585513, -1.4151447594625797, -0.794016729366608, 0.6432671881783553, 0.964900782267533, 1.2865343763567105, 1.6904953910172786, 2.31162342111325, 3.243315466257208]);
+
+        let res = Basic::batch_norm_running::<Array<f64>>(&a, &running_mean, &running_var, &weight, &bias, 1e-5).unwrap();
+        assert_close(res, &[0.35355250671311184, 1.0606575201393353, 1.7677625335655591, 14.641994204496, 17.4703930453952, 20.2987918862944, 4.596182587270453, 5.303287600696677, 6.0103926141229005, 31.6123872498912, 34.4407860907904, 38.6833843521392]);
+
+        let res = Basic::batch_norm_running_mean::<Array<f64>>(&a, &running_mean, 0.1).unwrap();
+        assert_close(res, &[0.95, -0.09166666666666656]);
+
+        let res = Basic::batch_norm_running_var::<Array<f64>>(&a, &running_var, 0.1).unwrap();
+        assert_close(res, &[2.96, 1.6941666666666666]);
+    }
+
+    #[test]
+    pub fn other_norms() {
+        //Both rows normalise to +-sqrt(3 / 2) around their mean
+        let a = Array::from_slice(&[1., 2., 3., 4., 6., 8.], shape![2, 3]);
+        let weight = Array::from_slice(&[1., 2., 1.], shape![3]);
+        let bias = Array::from_slice(&[0., 0., 1.], shape![3]);
+
+        let res = Basic::layer_norm::<Array<f64>>(&a, &shape![3], &weight, &bias, 0.).unwrap();
+        assert_close(res, &[-1.224744871391589, 0., 2.224744871391589, -1.224744871391589, 0., 2.224744871391589]);
+        assert!(Basic::layer_norm::<Array<f64>>(&a, &shape![2], &weight, &bias, 0.).is_err());
+        assert!(Basic::layer_norm::<Array<f64>>(&a, &shape![], &weight, &bias, 0.).is_err());
+
+        //Normalised over the trailing two dims together
+        let a = Array::from_slice(&[0., -0.5, -1., 1.5, 1., 0.5, 3., 2.5], shape![2, 2, 2]);
+        let ones = Array::from_slice(&[1., 1., 1., 1.], shape![2, 2]);
+        let zeros = Array::from_slice(&[0., 0., 0., 0.], shape![2, 2]);
+
+        let res = Basic::layer_norm::<Array<f64>>(&a, &shape![2, 2], &ones, &zeros, 0.).unwrap();
+        assert_close(res, &[0., -0.5345224838248488, -1.0690449676496976, 1.6035674514745464, -0.7276068751089989, -1.212678125181665, 1.212678125181665, 0.7276068751089989]);
+
+        let a = Array::from_slice(&[1., 2., 3., 5.], shape![1, 4, 1]);
+        let weight = Array::from_slice(&[1., 1., 1., 1.], shape![4]);
+        let bias = Array::from_slice(&[0., 0., 0., 0.], shape![4]);
+
+        let res = Basic::group_norm::<Array<f64>>(&a, 2, &weight, &bias, 0.).unwrap();
+        assert_close(res, &[-1., 1., -1., 1.]);
+        assert!(Basic::group_norm::<Array<f64>>(&a, 3, &weight, &bias, 0.).is_err());
+        assert!(matches!(Basic::group_norm::<Array<f64>>(&a, 2, weight.reshape(&shape![2, 2]).as_ref(), &bias, 0.), Err(EngineError::NumDimensionsMismatch(2, 1))));
+
+        //Two channels per group, each with its own weight and bias, eps included
+        let a = Array::from_iter((0..16).map(|i| i as f64 * 0.5 - (i % 3) as f64), shape![2, 4, 2]);
+        let weight = Array::from_slice(&[1., 2., -1., 0.5], shape![4]);
+        let bias = Array::from_slice(&[0., 1., 0.5, -1.], shape![4]);
+
+        let res = Basic::group_norm::<Array<f64>>(&a, 2, &weight, &bias, 1e-5).unwrap();
+        assert_close(res, &[0., -0.534519429436836, -1.1380777177473438, 4.207116576621015, 1.2276034511008147, 1.712672418501358, -0.393663790749321, -0.6361982744495926, -1.6035582883105077, 1.069038858873672, 2.0690388588736717, 1., 0.5, 1.0345194294368358, -1.5345194294368358, -0.19822085584474614]);
+
+        //The second channel has no variance so only eps keeps it finite
+        let a = Array::from_slice(&[1., 3., 2., 2.], shape![1, 2, 2]);
+        let weight = Array::from_slice(&[1., 1.], shape![2]);
+        let bias = Array::from_slice(&[0., 0.], shape![2]);
+
+        let res = Basic::instance_norm::<Array<f64>>(&a, &weight, &bias, 1e-5).unwrap();
+        assert_close(res, &[-0.9999950000374997, 0.9999950000374997, 0., 0.]);
+
+        //Hand computed: x / sqrt(12.5)
+        let a = Array::from_slice(&[3., 4.], shape![1, 2]);
+
+        let res = Basic::rms_norm::<Array<f64>>(&a, &shape![2], &weight, 0.).unwrap();
+        assert_close(res, &[0.848528137423857, 1.131370849898476]);
+    }
+
+    #[test]
+    pub fn pooling() {
+        let a = Array::from_slice(&[1., 5., 2., 0., 3., 4., 8., 1., 0., 2., 7., 6., 9., 1., 3., 5.], shape![1, 1, 4, 4]);
+
+        let res = Basic::max_pool2d::<Array<f64>>(&a, (2, 2), (2, 2), (0, 0), (1, 1)).unwrap();
+        assert!(res == Array::from_slice(&[5., 8., 9., 7.], shape![1, 1, 2, 2]).generic());
+
+        let indices = Basic::max_pool2d_indices::<Array<usize>>(&a, (2, 2), (2, 2), (0, 0), (1, 1)).unwrap();
+        assert!(indices == Array::from_slice(&[1, 6, 12, 10], shape![1, 1, 2, 2]).generic());
+
+        let res = Basic::max_unpool2d::<Array<f64>>(res.as_ref(), indices.as_ref(), (4, 4)).unwrap();
+        assert!(res == Array::from_slice(&[0., 5., 0., 0., 0., 0., 8., 0., 0., 0., 7., 0., 9., 0., 0., 0.], shape![1, 1, 4, 4]).generic());
+
+        let res = Basic::max_pool2d::<Array<f64>>(&a, (3, 3), (1, 1), (1, 1), (1, 1)).unwrap();
+        assert!(res == Array::from_slice(&[5., 8., 8., 8., 5., 8., 8., 8., 9., 9., 8., 8., 9., 9., 7., 7.], shape![1, 1, 4, 4]).generic());
+
+        let res = Basic::max_pool2d::<Array<f64>>(&a, (2, 2), (1, 1), (0, 0), (2, 2)).unwrap();
+        assert!(res == Array::from_slice(&[7., 6., 9., 5.], shape![1, 1, 2, 2]).generic());
+
+        assert!(matches!(Basic::max_pool2d::<Array<f64>>(&a, (2, 2), (0, 1), (0, 0), (1, 1)), Err(EngineError::ZeroWindow(2, 0, 1))));
+        assert!(matches!(Basic::avg_pool2d::<Array<f64>>(&a, (2, 0), (1, 1), (0, 0), (1, 1), false), Err(EngineError::ZeroWindow(0, 1, 1))));
+        assert!(matches!(Basic::max_pool2d_indices::<Array<usize>>(&a, (2, 2), (1, 1), (0, 0), (0, 1)), Err(EngineError::ZeroWindow(2, 1, 0))));
+
+        let res = Basic::avg_pool2d::<Array<f64>>(&a, (3, 3), (2, 2), (1, 1), (1, 1), true).unwrap();
+        assert_close(res, &[1.4444444444444444, 2.2222222222222223, 2.111111111111111, 4.111111111111111]);
+
+        let res = Basic::avg_pool2d::<Array<f64>>(&a, (3, 3), (2, 2), (1, 1), (1, 1), false).unwrap();
+        assert_close(res, &[3.25, 3.3333333333333335, 3.1666666666666665, 4.111111111111111]);
+
+        let res = Basic::adaptive_avg_pool2d::<Array<f64>>(&a, (3, 3)).unwrap();
+        assert_eq!(*res.shape(), shape![1, 1, 3, 3]);
+        assert_close(res, &[3.25, 4.75, 2.75, 2.25, 5.25, 5.5, 3.0, 3.25, 5.25]);
+
+        let res = Basic::adaptive_max_pool2d::<Array<f64>>(&a, (1, 1)).unwrap();
+        assert!(res == Array::from_slice(&[9.], shape![1, 1, 1, 1]).generic());
+
+        assert!(matches!(Basic::max_pool2d::<Array<f64>>(&a, (2, 2), (1, 1), (2, 2), (1, 1)), Err(EngineError::PaddingTooLarge(2, 2))));
+    }
+}
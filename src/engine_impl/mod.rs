@@ -1,5 +1,5 @@
 pub mod tensor;
 pub mod basic;
+pub mod util;
 
-mod shared;
-mod util;
\ No newline at end of file
+mod shared;
\ No newline at end of file
@@ -1,92 +1,624 @@
-use crate::{engine::{tensor::{builder::EngineTensorBuilder, factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible}, helper::{position, Interval, Position, Shape, Slice, Stride, VarArrayCompatible}};
+use std::iter;
 
-use super::tensor::padded::Padded;
+use crate::{engine::{tensor::{builder::EngineTensorBuilder, factory::EngineTensorFactory, unit_iter::EngineTensorUnitIterator, EngineTensor}, unit::{core_value::CoreValue, UnitCompatible}, ConvParams, EngineError, Reduction}, helper::{shape, Interval, Shape, Slice, Stride, VarArrayCompatible}};
 
-//a: (batches, in_channels, img_y, img_x)
-//kernel_shape: (in_channels, k_y, k_x)
-//out: (batches, in_channels, out_y, out_x, k_y * k_x)
-pub fn im2col_2d<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+use super::util::{conv_shape, conv_transpose_shape, err_if_dimension_mismatch, err_if_incorrect_num_dimensions, err_if_invalid_index_shape, err_if_invalid_padding_idx, err_if_too_few_dimensions, reduced_axes, reduced_axes_shape, return_if_matched_shape, unit_to_index, window_output_dim};
+
+//a: (batches, in_channels, *spatial)
+//out: (batches, in_channels, *out_spatial, kernel elements)
+//Positions falling in the padding are zero
+pub fn im2col<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    a: &dyn EngineTensor<Unit = T>,
+    kernel_spatial: &[usize],
+    stride: &[usize],
+    padding: &[usize],
+    dilation: &[usize],
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let spatial_dims = kernel_spatial.len();
+
+    err_if_incorrect_num_dimensions(a.shape(), spatial_dims + 2)?;
+
+    let planes = a.shape().get(0)? * a.shape().get(1)?;
+    let a_spatial = &a.shape().as_slice()[2..];
+
+    let out_spatial = (0..spatial_dims).map(|i| window_output_dim(a_spatial[i], kernel_spatial[i], stride[i], padding[i], dilation[i])).collect::<Result<Vec<_>, _>>()?;
+
+    let plane_len = a_spatial.iter().product::<usize>();
+    let out_len = out_spatial.iter().product::<usize>();
+    let patch_len = kernel_spatial.iter().product::<usize>();
+
+    let a_stride = &Stride::default_from_shape(&Shape::from(a_spatial));
+    let out_positions = Slice::new(iter::repeat(Interval::all()).take(spatial_dims).collect(), Shape::from(out_spatial.as_slice()));
+    let kernel_positions = Slice::new(iter::repeat(Interval::all()).take(spatial_dims).collect(), Shape::from(kernel_spatial));
+
+    //For every output position and kernel position the index into a's plane, None when in the padding
+    let gather = out_positions.iter().flat_map(|out_pos| {
+        kernel_positions.iter().map(move |kernel_pos| {
+            (0..spatial_dims).map(|i| {
+                let padded = out_pos.get(i).unwrap() * stride[i] + kernel_pos.get(i).unwrap() * dilation[i];
+
+                padded.checked_sub(padding[i]).filter(|p| *p < a_spatial[i]).map(|p| p * a_stride.get(i).unwrap())
+            }).sum::<Option<usize>>()
+        }).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    let a_data = a.iter_units().collect::<Vec<_>>();
+
+    let out_data = (0..planes).flat_map(|plane| {
+        let a_plane = &a_data[(plane * plane_len)..((plane + 1) * plane_len)];
+
+        gather.iter().map(|index| index.map_or(T::zero(), |index| a_plane[index]))
+    });
+
+    let out_shape = Shape::from_iter(a.shape().iter().take(2).chain(out_spatial).chain([patch_len]));
+    debug_assert_eq!(out_shape.elements(), planes * out_len * patch_len);
+
+    Ok(E::from_iter(out_data, out_shape).generic())
+}
+
+//Inverse of im2col, overlapping patches are summed
+//cols: (batches, channels, *out_spatial, kernel elements)
+//out: (batches, channels, *image_spatial)
+//Units that land in the padding are dropped
+pub fn col2im<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    cols: &dyn EngineTensor<Unit = T>,
+    image_spatial: &[usize],
+    kernel_spatial: &[usize],
+    stride: &[usize],
+    padding: &[usize],
+    dilation: &[usize],
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let spatial_dims = kernel_spatial.len();
+
+    err_if_incorrect_num_dimensions(cols.shape(), spatial_dims + 3)?;
+
+    let patch_len = kernel_spatial.iter().product::<usize>();
+    err_if_dimension_mismatch(cols.shape().get(spatial_dims + 2)?, patch_len)?;
+
+    let planes = cols.shape().get(0)? * cols.shape().get(1)?;
+    let out_spatial = &cols.shape().as_slice()[2..(spatial_dims + 2)];
+
+    let plane_len = image_spatial.iter().product::<usize>();
+    let cols_plane_len = out_spatial.iter().product::<usize>() * patch_len;
+
+    let image_stride = &Stride::default_from_shape(&Shape::from(image_spatial));
+    let out_positions = Slice::new(iter::repeat(Interval::all()).take(spatial_dims).collect(), Shape::from(out_spatial));
+    let kernel_positions = Slice::new(iter::repeat(Interval::all()).take(spatial_dims).collect(), Shape::from(kernel_spatial));
+
+    //Same walk as im2col, each column unit goes back to where it was gathered from
+    let scatter = out_positions.iter().flat_map(|out_pos| {
+        kernel_positions.iter().map(move |kernel_pos| {
+            (0..spatial_dims).map(|i| {
+                let padded = out_pos.get(i).unwrap() * stride[i] + kernel_pos.get(i).unwrap() * dilation[i];
+
+                padded.checked_sub(padding[i]).filter(|p| *p < image_spatial[i]).map(|p| p * image_stride.get(i).unwrap())
+            }).sum::<Option<usize>>()
+        }).collect::<Vec<_>>()
+    }).collect::<Vec<_>>();
+
+    let cols_data = cols.iter_units().collect::<Vec<_>>();
+    let mut buffer = vec![T::zero(); planes * plane_len];
+
+    for plane in 0..planes {
+        let cols_plane = &cols_data[(plane * cols_plane_len)..((plane + 1) * cols_plane_len)];
+        let image_plane = &mut buffer[(plane * plane_len)..((plane + 1) * plane_len)];
+
+        for (x, index) in cols_plane.iter().zip(scatter.iter()) {
+            if let Some(index) = index {
+                image_plane[*index] = image_plane[*index] + *x;
+            }
+        }
+    }
+
+    let out_shape = Shape::from_iter(cols.shape().iter().take(2).chain(image_spatial.iter().copied()));
+
+    Ok(E::from_slice(&buffer, out_shape).generic())
+}
+
+//cols: (batches, channels, out_y, out_x, kernel_y * kernel_x)
+//out: (batches, channels, image_y, image_x)
+pub fn col2im_2d<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    cols: &dyn EngineTensor<Unit = T>,
+    image_size: [usize; 2],
+    kernel: [usize; 2],
+    stride: [usize; 2],
+    padding: [usize; 2],
+    dilation: [usize; 2],
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    col2im::<T, E>(cols, &image_size, &kernel, &stride, &padding, &dilation)
+}
+
+//a: (batches, in_channels, *spatial)
+//kernel: (in_channels, out_channels / groups, *kernel_spatial), bias: (out_channels)
+//Every input unit scales the kernel into a patch of the output, the patches are then summed by col2im
+pub fn conv_transpose<T: UnitCompatible, E: EngineTensorFactory<Unit = T>, const N: usize>(
+    a: &dyn EngineTensor<Unit = T>,
+    kernel: &dyn EngineTensor<Unit = T>,
+    bias: Option<&dyn EngineTensor<Unit = T>>,
+    params: &ConvParams<N>,
+    output_padding: &[usize; N],
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let out_shape = conv_transpose_shape(a.shape(), kernel.shape(), params, output_padding)?;
+    let ConvParams { stride, padding, dilation, groups } = *params;
+
+    let batches = a.shape().get(0)?;
+    let in_channels = a.shape().get(1)?;
+    let out_channels = out_shape.get(1)?;
+
+    if let Some(bias) = bias {
+        return_if_matched_shape(bias.shape(), &shape![out_channels], ())?;
+    }
+
+    let in_group = in_channels / groups;
+    let out_group = out_channels / groups;
+
+    let kernel_spatial = &kernel.shape().as_slice()[2..];
+    let patch_len = kernel_spatial.iter().product::<usize>();
+    let a_len = a.shape().as_slice()[2..].iter().product::<usize>();
+
+    let a_data = a.iter_units().collect::<Vec<_>>();
+    let kernels = kernel.iter_units().collect::<Vec<_>>();
+
+    //(batches, out_channels, *spatial, patch_len)
+    let cols_data = (0..batches).flat_map(|batch| (0..out_channels).map(move |out_channel| (batch, out_channel))).flat_map(|(batch, out_channel)| {
+        let group = out_channel / out_group;
+        let group_out_channel = out_channel % out_group;
+
+        let a_data = &a_data;
+        let kernels = &kernels;
+
+        (0..a_len).flat_map(move |a_pos| {
+            (0..patch_len).map(move |k| {
+                (0..in_group).map(|group_channel| {
+                    let in_channel = group * in_group + group_channel;
+
+                    a_data[(batch * in_channels + in_channel) * a_len + a_pos] * kernels[(in_channel * out_group + group_out_channel) * patch_len + k]
+                }).sum::<T>()
+            })
+        })
+    });
+
+    let cols_shape = Shape::from_iter([batches, out_channels].into_iter().chain(a.shape().iter().skip(2)).chain([patch_len]));
+    let cols = E::from_iter(cols_data, cols_shape).generic();
+
+    let out = col2im::<T, E>(cols.as_ref(), &out_shape.as_slice()[2..], kernel_spatial, &stride, &padding, &dilation)?;
+
+    match bias {
+        Some(bias) => {
+            let biases = bias.iter_units().collect::<Vec<_>>();
+            let plane_len = out_shape.as_slice()[2..].iter().product::<usize>();
+
+            Ok(E::from_iter(out.iter_units().enumerate().map(|(i, x)| x + biases[(i / plane_len) % out_channels]), out_shape).generic())
+        },
+        None => Ok(out),
+    }
+}
+
+//Shared by every spatial rank, see Engine::conv2d
+pub fn conv<T: UnitCompatible, E: EngineTensorFactory<Unit = T>, const N: usize>(
     a: &dyn EngineTensor<Unit = T>,
-    kernel_shape: &Shape,
-    padding: usize,
-    stride: usize,
+    kernel: &dyn EngineTensor<Unit = T>,
+    bias: Option<&dyn EngineTensor<Unit = T>>,
+    params: &ConvParams<N>,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let out_shape = conv_shape(a.shape(), kernel.shape(), params)?;
+    let ConvParams { stride, padding, dilation, groups } = *params;
+
+    let batches = a.shape().get(0)?;
+    let in_channels = a.shape().get(1)?;
+    let out_channels = kernel.shape().get(0)?;
+
+    if let Some(bias) = bias {
+        return_if_matched_shape(bias.shape(), &shape![out_channels], ())?;
+    }
+
+    let in_group = in_channels / groups;
+    let out_group = out_channels / groups;
+
+    //(batches, in_channels, *out_spatial, patch_len)
+    let cols = im2col::<T, E>(a, &kernel.shape().as_slice()[2..], &stride, &padding, &dilation)?.iter_units().collect::<Vec<_>>();
+    let kernels = kernel.iter_units().collect::<Vec<_>>();
+    let biases = bias.map(|b| b.iter_units().collect::<Vec<_>>());
+
+    let out_len = out_shape.as_slice()[2..].iter().product::<usize>();
+    let patch_len = kernels.len() / (out_channels * in_group);
+
+    let out_data = (0..batches).flat_map(|batch| (0..out_channels).map(move |out_channel| (batch, out_channel))).flat_map(|(batch, out_channel)| {
+        let group = out_channel / out_group;
+        let bias = biases.as_ref().map_or(T::zero(), |b| b[out_channel]);
+
+        let cols = &cols;
+        let kernels = &kernels;
+
+        (0..out_len).map(move |out_pos| {
+            (0..in_group).map(|group_channel| {
+                let in_channel = group * in_group + group_channel;
+
+                let col_start = ((batch * in_channels + in_channel) * out_len + out_pos) * patch_len;
+                let kernel_start = (out_channel * in_group + group_channel) * patch_len;
+
+                cols[col_start..(col_start + patch_len)].iter().zip(kernels[kernel_start..(kernel_start + patch_len)].iter()).map(|(x, k)| *x * *k).sum::<T>()
+            }).sum::<T>() + bias
+        })
+    });
+
+    Ok(E::from_iter(out_data, out_shape).generic())
+}
+
+//Unreduced losses keep the shape they were computed with, reduced losses have shape (1)
+pub fn reduce_loss<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    losses: impl Iterator<Item = T>,
+    shape: Shape,
+    reduction: Reduction,
 ) -> Box<dyn EngineTensor<Unit = T>> {
-    let batches = a.shape().get(0).unwrap();
-    let in_channels = a.shape().get(1).unwrap();
+    match reduction {
+        Reduction::None => E::from_iter(losses, shape).generic(),
+        Reduction::Sum => E::from_slice(&[losses.sum::<T>()], shape![1]).generic(),
+        Reduction::Mean => {
+            let elements = T::from(shape.elements());
 
-    //Ok if zero padding
-    let a_padded = Padded::pad_from(
-        a.clone(),
-        [0, 0, padding, padding].as_slice().into(),
-        T::zero(),
-    );
+            E::from_slice(&[losses.sum::<T>() / elements], shape![1]).generic()
+        },
+    }
+}
 
-    let img_y = a_padded.shape().get(2).unwrap();
-    let img_x = a_padded.shape().get(3).unwrap();
+//Range of a dimension covered by a window as (start, end, step)
+//Positions that fall in the padding are left out
+pub fn pool_window(out: usize, dim: usize, kernel: usize, stride: usize, padding: usize, dilation: usize) -> (usize, usize, usize) {
+    //Start in padded coordinates
+    let padded_start = out * stride;
 
-    let k_y = kernel_shape.get(1).unwrap();
-    let k_x = kernel_shape.get(2).unwrap();
+    let positions = (0..kernel).map(|k| padded_start + k * dilation).filter(|p| (padding..(dim + padding)).contains(p)).map(|p| p - padding);
 
-    let out_y = (img_y - k_y) / stride + 1;
-    let out_x = (img_x - k_x) / stride + 1;
+    let (first, last) = positions.fold((None, None), |(first, _), p| (first.or(Some(p)), Some(p)));
 
-    let patch_len = k_y * k_x;
+    match (first, last) {
+        (Some(first), Some(last)) => (first, last + 1, dilation),
+        _ => (0, 0, dilation),
+    }
+}
 
-    let out_shape = Shape::from([batches, in_channels, out_y, out_x, patch_len].as_slice());
-    //let out_stride = Stride::default_from_shape(&out_shape);
+//Range of a dimension covered by an adaptive window, windows overlap when they don't divide evenly
+pub fn adaptive_pool_window(out: usize, dim: usize, out_dim: usize) -> (usize, usize, usize) {
+    let start = (out * dim) / out_dim;
+    let end = ((out + 1) * dim).div_ceil(out_dim);
 
-    //let final_img_dims = Shape::new(kernel_shape.iter().zip(img_dims.iter()).map(|(k_d, a_d)| (a_d + 2 * padding - k_d) / stride + 1).collect());
+    (start, end, 1)
+}
 
-    let grouped_patches_shape = Shape::from([batches, in_channels, patch_len].as_slice());
+//a: (batches, channels, y, x)
+//out: (batches, channels, out_y, out_x)
+//Every window is sliced out of a for all batches and channels at once, fold then sees the units of each plane's window
+//paired with their index in the flattened (y, x) plane
+pub fn pool_2d<T: UnitCompatible, E: EngineTensorFactory>(
+    a: &dyn EngineTensor<Unit = T>,
+    out_size: (usize, usize),
+    window_y: impl Fn(usize) -> (usize, usize, usize),
+    window_x: impl Fn(usize) -> (usize, usize, usize),
+    fold: impl Fn(&mut dyn Iterator<Item = (usize, T)>) -> E::Unit,
+) -> Result<Box<dyn EngineTensor<Unit = E::Unit>>, EngineError> {
+    err_if_incorrect_num_dimensions(a.shape(), 4)?;
 
-    //Buffer used for output
+    let batches = a.shape().get(0)?;
+    let channels = a.shape().get(1)?;
+    let img_x = a.shape().get(3)?;
 
-    //let mut buffer = Vec::<T>::from_iter(iter::repeat(T::zero()).take(out_shape.elements()));
-    //buffer.shrink_to_fit();
+    let (out_y, out_x) = out_size;
+    let out_shape = Shape::from([batches, channels, out_y, out_x].as_slice());
 
-    let mut builder = E::builder(out_shape.clone(), T::default());
+    let mut buffer = vec![<E::Unit as CoreValue>::zero(); out_shape.elements()];
 
     for y in 0..out_y {
+        let (y_start, y_end, y_step) = window_y(y);
+
         for x in 0..out_x {
-            let grouped_patches = a_padded.slice(
-                [
-                    Interval::all(),
-                    Interval::all(),
-                    Interval::between_with_step(y, y + k_y, stride),
-                    Interval::between_with_step(x, x + k_x, stride),
-                ]
-                .as_slice(),
-            );
-
-            let grouped_patches = grouped_patches.reshape(&grouped_patches_shape);
+            let (x_start, x_end, x_step) = window_x(x);
+
+            let window = a.slice(&[
+                Interval::all(),
+                Interval::all(),
+                Interval::between_with_step(y_start, y_end, y_step),
+                Interval::between_with_step(x_start, x_end, x_step),
+            ]);
+
+            let plane_indices = (y_start..y_end).step_by(y_step).flat_map(|iy| (x_start..x_end).step_by(x_step).map(move |ix| iy * img_x + ix)).collect::<Vec<_>>();
 
             for batch in 0..batches {
-                for channel in 0..in_channels {
-                    let patch = grouped_patches.slice(
-                        &[
-                            Interval::only(batch),
-                            Interval::only(channel),
-                            Interval::all(),
-                        ],
-                    );
-
-                    //let start_index = Position::from([batch, channel, y, x, 0].as_slice())
-                    //    .tensor_index(&out_stride)
-                    //    .unwrap();
-
-                    //buffer.splice(start_index..(start_index + patch_len), patch.iter_units());
-
-                    let start_pos = position![batch, channel, y, x, 0];
-                    let last_pos = position![batch, channel, y, x, patch_len - 1];
-                    builder.splice_between_positions(&start_pos, &last_pos, patch.iter_units());
+                for channel in 0..channels {
+                    let plane_window = window.slice(&[Interval::only(batch), Interval::only(channel), Interval::all(), Interval::all()]);
+                    let mut units = plane_indices.iter().copied().zip(plane_window.iter_units());
+
+                    *buffer.get_mut(((batch * channels + channel) * out_y + y) * out_x + x).unwrap() = fold(&mut units);
                 }
             }
         }
     }
 
-    //E::from_slice(buffer.as_slice(), out_shape).generic()
+    Ok(E::from_slice(&buffer, out_shape).generic())
+}
+
+//Intervals of the sub tensor at every position of the axes which aren't reduced, the reduced axes are sliced whole
+fn reduced_sub_intervals(a: &Shape, axes: &[usize]) -> Result<Vec<Vec<Interval>>, EngineError> {
+    let reduced = reduced_axes(a, axes)?;
 
-    builder.construct().generic()
+    let kept_shape = Shape::from_iter(a.iter().zip(reduced.iter()).map(|(d, r)| if *r { 1 } else { d }));
+    let kept_slice = Slice::new(iter::repeat(Interval::all()).take(kept_shape.len()).collect(), kept_shape);
+
+    Ok(kept_slice.iter().map(|pos| {
+        pos.iter().zip(reduced.iter()).map(|(x, r)| if *r { Interval::all() } else { Interval::only(x) }).collect()
+    }).collect())
+}
+
+//a: (batches, channels, *), every axis but the channels, which batch norm reduces over
+pub fn non_channel_axes(a: &Shape) -> Result<Vec<usize>, EngineError> {
+    err_if_too_few_dimensions(a, 2)?;
+
+    Ok(iter::once(0).chain(2..a.len()).collect())
+}
+
+//Views per channel parameters: (channels) as (channels, 1, ...) so they broadcast against a: (batches, channels, *)
+pub fn channel_parameter<T: UnitCompatible>(param: &dyn EngineTensor<Unit = T>, a: &Shape) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    err_if_too_few_dimensions(a, 2)?;
+    err_if_incorrect_num_dimensions(param.shape(), 1)?;
+
+    let channels = a.get(1)?;
+
+    err_if_dimension_mismatch(param.shape().get(0)?, channels)?;
+
+    Ok(param.reshape(&Shape::from_iter(iter::once(channels).chain(iter::repeat(1).take(a.len() - 2)))))
+}
+
+//Mean and biased variance at every position of the axes which aren't reduced, along with the number of units each was computed over
+//Without centring the mean is left at zero so the variance is the mean square RMS normalisation uses
+pub fn channel_statistics<T: UnitCompatible>(a: &dyn EngineTensor<Unit = T>, axes: &[usize], centre: bool) -> Result<(Vec<T>, Vec<T>, usize), EngineError> {
+    let reduced = reduced_axes(a.shape(), axes)?;
+    let elements = a.shape().iter().zip(reduced.iter()).filter(|(_, r)| **r).map(|(d, _)| d).product::<usize>();
+    let count = T::from(elements);
+
+    let (means, variances) = reduced_sub_intervals(a.shape(), axes)?.iter().map(|intervals| {
+        let sub = a.slice(intervals);
+
+        let mean = if centre { sub.iter_units().sum::<T>() / count } else { T::zero() };
+        let variance = sub.iter_units().map(|x| (x - mean) * (x - mean)).sum::<T>() / count;
+
+        (mean, variance)
+    }).unzip();
+
+    Ok((means, variances, elements))
+}
+
+//(x - mean) / sqrt(variance + eps) * weight + bias with the statistics of every position of the axes which aren't reduced
+//weight and bias are broadcast to the shape of a
+pub fn normalise_channels<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    a: &dyn EngineTensor<Unit = T>,
+    axes: &[usize],
+    means: &[T],
+    variances: &[T],
+    weight: &dyn EngineTensor<Unit = T>,
+    bias: &dyn EngineTensor<Unit = T>,
+    eps: f64,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let positions = reduced_sub_intervals(a.shape(), axes)?;
+
+    err_if_dimension_mismatch(means.len(), positions.len())?;
+    err_if_dimension_mismatch(variances.len(), positions.len())?;
+
+    let weight = weight.broadcast_to(a.shape())?;
+    let bias = bias.broadcast_to(a.shape())?;
+
+    let eps = T::one().scale_double(eps);
+    let mut builder = E::builder(a.shape().clone(), T::zero());
+
+    for ((intervals, mean), variance) in positions.iter().zip(means.iter()).zip(variances.iter()) {
+        let (sub, sub_weight, sub_bias) = (a.slice(intervals), weight.slice(intervals), bias.slice(intervals));
+        let std = (*variance + eps).sqrt();
+
+        let norms = sub.iter_units().zip(sub_weight.iter_units()).zip(sub_bias.iter_units()).map(|((x, w), b)| ((x - *mean) / std) * w + b);
+
+        builder.splice_slice(intervals, norms);
+    }
+
+    Ok(builder.construct().generic())
+}
+
+//Views both tensors with their broadcast shape, tensors already of that shape are only shallow cloned
+pub fn broadcast_pair<T: UnitCompatible>(
+    a: &dyn EngineTensor<Unit = T>,
+    b: &dyn EngineTensor<Unit = T>,
+) -> Result<(Box<dyn EngineTensor<Unit = T>>, Box<dyn EngineTensor<Unit = T>>), EngineError> {
+    let out_shape = a.shape().broadcast_with(b.shape()).ok_or_else(|| EngineError::ShapeMismatch(a.shape().clone(), b.shape().clone()))?;
+
+    let expand = |x: &dyn EngineTensor<Unit = T>| {
+        if *x.shape() == out_shape {
+            Ok(x.clone())
+        } else {
+            x.broadcast_to(&out_shape)
+        }
+    };
+
+    Ok((expand(a)?, expand(b)?))
+}
+
+pub fn broadcast_zip<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    a: &dyn EngineTensor<Unit = T>,
+    b: &dyn EngineTensor<Unit = T>,
+    op: impl Fn(T, T) -> T,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let (a, b) = broadcast_pair(a, b)?;
+
+    Ok(E::from_iter(a.iter_units().zip(b.iter_units()).map(|(x, y)| op(x, y)), a.shape().clone()).generic())
+}
+
+pub fn bool_unit<T: UnitCompatible>(x: bool) -> T {
+    if x { T::one() } else { T::zero() }
+}
+
+//Folds the units of every sub tensor spanning the reduced axes into a single unit
+//Reduced axes are sliced whole while the remaining axes are walked one position at a time
+pub fn reduce_axes<T: UnitCompatible, E: EngineTensorFactory>(
+    a: &dyn EngineTensor<Unit = T>,
+    axes: &[usize],
+    keep_dims: bool,
+    fold: impl Fn(EngineTensorUnitIterator<'_, T>) -> Option<E::Unit>,
+) -> Result<Box<dyn EngineTensor<Unit = E::Unit>>, EngineError> {
+    let out_shape = reduced_axes_shape(a.shape(), axes, keep_dims)?;
+
+    let units = reduced_sub_intervals(a.shape(), axes)?.iter().map(|intervals| {
+        let sub = a.slice(intervals);
+
+        fold(sub.iter_units()).ok_or_else(|| EngineError::EmptyReduction(a.shape().clone()))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(E::from_slice(&units, out_shape).generic())
+}
+
+//Walks every row along axis, the row max is subtracted before exp so large units can't overflow
+//normalise is given each unit with its row's max and sum of exp(x - max)
+pub fn softmax_axis<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    a: &dyn EngineTensor<Unit = T>,
+    axis: usize,
+    normalise: impl Fn(T, T, T) -> T,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    err_if_too_few_dimensions(a.shape(), axis + 1)?;
+
+    let dim = a.shape().get(axis)?;
+    let rest = a.shape().iter().skip(axis + 1).product::<usize>();
+
+    //Nothing to normalise, also covers dim == 0 where there's no first unit to start the max from
+    if a.shape().elements() == 0 {
+        return Ok(E::from_slice(&[], a.shape().clone()).generic());
+    }
+
+    let leading = iter::repeat(Interval::all()).take(axis).collect::<Vec<_>>();
+    let mut units = Vec::with_capacity(a.shape().elements());
+
+    //Each sub tensor is laid out (axis, rest) so a row is every rest'th unit
+    for sub in a.iter_sub_tensor(&leading) {
+        let rows = sub.iter_units().collect::<Vec<_>>();
+
+        let maxes = (0..rest).map(|r| {
+            (1..dim).map(|i| rows[i * rest + r]).fold(rows[r], |m, x| if x > m { x } else { m })
+        }).collect::<Vec<_>>();
+
+        let sums = (0..rest).map(|r| {
+            (0..dim).map(|i| (rows[i * rest + r] - maxes[r]).exp()).sum::<T>()
+        }).collect::<Vec<_>>();
+
+        units.extend(rows.iter().enumerate().map(|(i, x)| normalise(*x, maxes[i % rest], sums[i % rest])));
+    }
+
+    Ok(E::from_slice(&units, a.shape().clone()).generic())
+}
+
+//1 where a query can't attend to a key, either masked out or after the query's position plus the causal offset
+//scores: (*, target_len, source_len), mask is already broadcast to the shape of scores
+pub fn attention_blocked<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    scores: &Shape,
+    mask: Option<&dyn EngineTensor<Unit = T>>,
+    causal_offset: Option<usize>,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    err_if_too_few_dimensions(scores, 2)?;
+
+    let target_len = scores.get(scores.len() - 2)?;
+    let source_len = scores.get(scores.len() - 1)?;
+
+    let mask = mask.map(|mask| mask.iter_units().collect::<Vec<_>>());
+
+    let blocked = (0..scores.elements()).map(|i| {
+        let (query, key) = ((i / source_len) % target_len, i % source_len);
+
+        let masked = mask.as_ref().is_some_and(|mask| mask[i] == T::zero());
+        let later = causal_offset.is_some_and(|offset| key > query + offset);
+
+        bool_unit(masked || later)
+    });
+
+    Ok(E::from_iter(blocked, scores.clone()).generic())
+}
+
+//The row of weight for every id (in iteration order), None for padding
+pub fn embedding_rows<T: UnitCompatible, I: UnitCompatible>(
+    weight: &dyn EngineTensor<Unit = T>,
+    ids: &dyn EngineTensor<Unit = I>,
+    padding_idx: Option<usize>,
+    max_norm: Option<f64>,
+) -> Result<Vec<Option<Vec<T>>>, EngineError> {
+    err_if_incorrect_num_dimensions(weight.shape(), 2)?;
+
+    let num_embeddings = weight.shape().get(0)?;
+    let embedding_dim = weight.shape().get(1)?;
+    let weight = weight.iter_units().collect::<Vec<_>>();
+
+    err_if_invalid_padding_idx(padding_idx, num_embeddings)?;
+
+    ids.iter_units().map(|id| {
+        let id = unit_to_index(id)?;
+
+        if id >= num_embeddings {
+            return Err(EngineError::IndexOutOfRange(id, num_embeddings));
+        }
+
+        if Some(id) == padding_idx {
+            return Ok(None);
+        }
+
+        let row = &weight[(id * embedding_dim)..((id + 1) * embedding_dim)];
+        let norm = row.iter().map(|x| *x * *x).sum::<T>().sqrt();
+
+        //Same rescaling as PyTorch, including its small offset to the norm
+        let row = match max_norm.map(|max| T::one().scale_double(max)) {
+            Some(max) if norm > max => row.iter().map(|x| *x * (max / (norm + T::one().scale_double(1e-7)))).collect(),
+            _ => row.to_vec(),
+        };
+
+        Ok(Some(row))
+    }).collect()
+}
+
+//Index of a's units (in iteration order) for every unit of index, which replaces the position along axis
+pub fn axis_index_positions(a: &Shape, axis: usize, index: &dyn EngineTensor<Unit = usize>) -> Result<Vec<usize>, EngineError> {
+    err_if_invalid_index_shape(a, axis, index.shape())?;
+
+    let a_stride = Stride::default_from_shape(a);
+    let axis_dim = a.get(axis)?;
+
+    let positions = Slice::new(iter::repeat(Interval::all()).take(a.len()).collect(), index.shape().clone());
+
+    positions.iter().zip(index.iter_units()).map(|(pos, i)| {
+        if i >= axis_dim {
+            return Err(EngineError::IndexOutOfRange(i, axis_dim));
+        }
+
+        Ok(pos.iter().enumerate().map(|(d, p)| if d == axis { i } else { p } * a_stride.get(d).unwrap()).sum())
+    }).collect()
+}
+
+//Writes src into a copy of a at the positions picked by index
+pub fn scatter_with<T: UnitCompatible, E: EngineTensorFactory<Unit = T>>(
+    a: &dyn EngineTensor<Unit = T>,
+    axis: usize,
+    index: &dyn EngineTensor<Unit = usize>,
+    src: &dyn EngineTensor<Unit = T>,
+    combine: impl Fn(T, T) -> T,
+) -> Result<Box<dyn EngineTensor<Unit = T>>, EngineError> {
+    let positions = axis_index_positions(a.shape(), axis, index)?;
+
+    //Only the corner of src covered by index is used
+    err_if_incorrect_num_dimensions(src.shape(), index.shape().len())?;
+
+    if let Some((index_dim, src_dim)) = index.shape().iter().zip(src.shape().iter()).find(|(index_dim, src_dim)| index_dim > src_dim) {
+        return Err(EngineError::DimensionMismatch(index_dim, src_dim));
+    }
+
+    let src = src.slice(&index.shape().iter().map(|d| Interval::between(0, d)).collect::<Vec<_>>());
+
+    let mut units = a.iter_units().collect::<Vec<_>>();
+
+    for (position, x) in positions.into_iter().zip(src.iter_units()) {
+        units[position] = combine(units[position], x);
+    }
+
+    Ok(E::from_slice(&units, a.shape().clone()).generic())
 }
 
 #[cfg(test)]
@@ -96,7 +628,7 @@ mod test {
     use super::*;
 
     #[test]
-    fn simple_im2col_2d() {
+    fn simple_im2col() {
         //Pytorch generated im2col
         let expected: [f32; 972] = [
             0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 0.0, 4.0, 5.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0,
@@ -182,10 +714,21 @@ mod test {
             a_shape,
         );
 
-        let res = im2col_2d::<_, Array<_>>(a.generic().as_ref(), &kernel_shape, 1, 1);
+        let res = im2col::<_, Array<_>>(a.generic().as_ref(), &kernel_shape.as_slice()[1..], &[1, 1], &[1, 1], &[1, 1]).unwrap();
 
         for (res_element, expected_element) in res.iter_units().zip(expected.iter()) {
             assert_eq!(res_element, *expected_element);
         }
     }
+
+    #[test]
+    fn simple_col2im() {
+        let a = Array::from_iter(std::iter::repeat(1.).take(9), shape![1, 1, 3, 3]);
+
+        let cols = im2col::<f32, Array<_>>(&a, &[3, 3], &[1, 1], &[1, 1], &[1, 1]).unwrap();
+        let res = col2im_2d::<f32, Array<_>>(cols.as_ref(), [3, 3], [3, 3], [1, 1], [1, 1], [1, 1]).unwrap();
+
+        //Each unit is counted once for every window covering it
+        assert!(res == Array::from_slice(&[4., 6., 4., 6., 9., 6., 4., 6., 4.], shape![1, 1, 3, 3]).generic());
+    }
 }
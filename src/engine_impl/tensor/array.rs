@@ -6,6 +6,7 @@ use crate::{
             builder::EngineTensorBuilder, extension::{EmptyExtensionProvider, ExtensionProvider}, factory::EngineTensorFactory, sub_tensor_iter::EngineTensorSubTensorIterator, unit_iter::EngineTensorUnitIterator, EngineTensor
         },
         unit::UnitCompatible,
+        EngineError,
     },
     helper::{Interval, Position, Shape, Slice, Stride, VarArrayCompatible},
 };
@@ -31,28 +32,12 @@ impl<T: AllowedArray> Array<T> {
         }
     }
 
-    //Am I dumb?
-    //This is wrong!!!
-    //TODO
+    //Units are laid out in order if the stride matches the default, permuted and stepped tensors don't
+    //The stride of a dimension of size 1 is never used so it can be anything
     fn is_contiguous(&self) -> bool {
-        let mut check: Option<usize> = None;
-
-        for curr in self.stride.iter() {
-            match check {
-                Some(prev) => {
-                    if prev * prev == curr {
-                        check = Some(curr);
-                    } else {
-                        return false;
-                    }
-                }
-                None => {
-                    check = Some(curr);
-                }
-            }
-        }
+        let default_stride = Stride::default_from_shape(&self.shape);
 
-        return true;
+        self.shape.iter().zip(self.stride.iter().zip(default_stride.iter())).all(|(dim, (stride, default))| dim == 1 || stride == default)
     }
 }
 
@@ -95,10 +80,11 @@ impl<T: AllowedArray> EngineTensor for Array<T> {
     fn slice(&self, intervals: &[Interval]) -> Box<dyn EngineTensor<Unit = T>> {
         let slice = Slice::new(intervals.into(), self.shape().clone());
 
-        let offset = slice.start().tensor_index(&self.stride).unwrap();
+        let offset = self.offset + slice.start().tensor_index(&self.stride).unwrap();
+        let stride = Stride::new(self.stride.iter().zip(slice.as_boxed_slice().iter().map(|interval| interval.step()).chain(std::iter::repeat(1))).map(|(stride, step)| stride * step).collect());
 
         Box::from(Self {
-            stride: self.stride.clone(),
+            stride,
             shape: slice.inferred_shape(),
             data: self.data.clone(),
             offset,
@@ -165,6 +151,58 @@ impl<T: AllowedArray> EngineTensor for Array<T> {
         }
     }
 
+    //Prepended and expanded dimensions get a stride of 0 so every position reads the same unit
+    fn broadcast_to(&self, shape: &Shape) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        if shape.len() >= self.shape().len() {
+            let missing = shape.len() - self.shape().len();
+
+            let stride_buffer = shape.iter().enumerate().map(|(i, dim)| {
+                if i < missing {
+                    Some(0)
+                } else {
+                    let self_dim = self.shape().get(i - missing).unwrap();
+                    let self_stride = self.stride.get(i - missing).unwrap();
+
+                    if self_dim == dim {
+                        Some(self_stride)
+                    } else if self_dim == 1 {
+                        Some(0)
+                    } else {
+                        None
+                    }
+                }
+            }).collect::<Option<Vec<_>>>();
+
+            match stride_buffer {
+                Some(stride_buffer) => Ok(Box::new(Self {
+                    stride: Stride::new(stride_buffer.as_slice().into()),
+                    shape: shape.clone(),
+                    data: self.data.clone(),
+                    offset: self.offset,
+                })),
+                None => Err(EngineError::ShapeMismatch(self.shape().clone(), shape.clone())),
+            }
+        } else {
+            Err(EngineError::ShapeMismatch(self.shape().clone(), shape.clone()))
+        }
+    }
+
+    //Only the order of the shape and stride changes so no units are copied
+    fn permute(&self, dims: &[usize]) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        let num_dims = self.shape().len();
+
+        if dims.len() == num_dims && (0..num_dims).all(|d| dims.contains(&d)) {
+            Ok(Box::new(Self {
+                stride: Stride::from_iter(dims.iter().map(|d| self.stride.get(*d).unwrap())),
+                shape: Shape::from_iter(dims.iter().map(|d| self.shape().get(*d).unwrap())),
+                data: self.data.clone(),
+                offset: self.offset,
+            }))
+        } else {
+            Err(EngineError::InvalidPermutation(dims.into(), num_dims))
+        }
+    }
+
     fn extensions(&self) -> Box<dyn ExtensionProvider + '_> {
         Box::from(EmptyExtensionProvider::from(self))
     }
@@ -236,3 +274,76 @@ impl<T: AllowedArray> EngineTensorBuilder for ArrayBuilder<T> {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::helper::{shape, varr, VarArray};
+    use crate::engine_impl::tensor::padded::Padded;
+
+    use super::*;
+
+    #[test]
+    fn slice_of_slice() {
+        let a = Array::from_iter((0..12).map(|x| x as f32), shape![3, 4]);
+
+        //The second slice has to start from the first one's offset rather than the start of the data
+        let rows = a.slice(&[Interval::between(1, 3), Interval::all()]);
+        let inner = rows.slice(&[Interval::only(1), Interval::between(1, 3)]);
+
+        assert!(inner == Array::from_slice(&[9., 10.], shape![1, 2]).generic());
+    }
+
+    #[test]
+    fn slice_with_step() {
+        let a = Array::from_iter((0..12).map(|x| x as f32), shape![3, 4]);
+
+        //Stepped intervals have to skip units in the underlying data rather than read them in order
+        let stepped = a.slice(&[Interval::all(), Interval::between_with_step(0, 4, 2)]);
+        assert!(stepped == Array::from_slice(&[0., 2., 4., 6., 8., 10.], shape![3, 2]).generic());
+
+        let rows = a.slice(&[Interval::between_with_step(0, 3, 2), Interval::all()]);
+        assert!(rows == Array::from_slice(&[0., 1., 2., 3., 8., 9., 10., 11.], shape![2, 4]).generic());
+    }
+
+    #[test]
+    fn permute_views() {
+        let a = Array::from_iter((0..6).map(|x| x as f32), shape![2, 3]);
+
+        let transposed = a.transpose(0, 1).unwrap();
+        assert!(transposed == Array::from_slice(&[0., 3., 1., 4., 2., 5.], shape![3, 2]).generic());
+        assert!(a.swap_last_two().unwrap() == transposed.clone());
+        assert!(matches!(a.transpose(0, 2), Err(EngineError::IndexOutOfRange(2, 2))));
+
+        //Reshaping a permuted view has to follow the new order rather than the underlying data
+        assert!(transposed.reshape(&shape![6]) == Array::from_slice(&[0., 3., 1., 4., 2., 5.], shape![6]).generic());
+
+        let b = Array::from_iter((0..24).map(|x| x as f32), shape![2, 3, 4]);
+        let permuted = b.permute(&[2, 0, 1]).unwrap();
+
+        assert_eq!(*permuted.shape(), shape![4, 2, 3]);
+        assert_eq!(permuted.get(&Position::from([3, 1, 2].as_slice())), b.get(&Position::from([1, 2, 3].as_slice())));
+        assert!(permuted.permute(&[1, 2, 0]).unwrap() == b.clone());
+
+        let padded = Padded::pad_from(a.generic(), varr![0, 1], 0.);
+        assert!(padded.transpose(0, 1).unwrap() == Array::from_slice(&[0., 0., 0., 3., 1., 4., 2., 5., 0., 0.], shape![5, 2]).generic());
+
+        //Vectors have nothing to swap
+        let v = Array::from_iter((0..3).map(|x| x as f32), shape![3]);
+        assert!(v.swap_last_two().unwrap() == v.clone());
+
+        assert!(matches!(b.permute(&[0, 0, 1]), Err(EngineError::InvalidPermutation(_, 3))));
+        assert!(matches!(b.permute(&[1, 0]), Err(EngineError::InvalidPermutation(_, 3))));
+    }
+
+    #[test]
+    fn broadcast_views() {
+        let a = Array::from_iter((0..3).map(|x| x as f32), shape![3, 1]);
+
+        let expanded = a.broadcast_to(&shape![2, 3, 2]).unwrap();
+        assert!(expanded == Array::from_slice(&[0., 0., 1., 1., 2., 2., 0., 0., 1., 1., 2., 2.], shape![2, 3, 2]).generic());
+
+        //Only dimensions of size 1 can expand and dimensions can't be removed
+        assert!(matches!(a.broadcast_to(&shape![2, 2]), Err(EngineError::ShapeMismatch(_, _))));
+        assert!(matches!(a.broadcast_to(&shape![3]), Err(EngineError::ShapeMismatch(_, _))));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{engine::{tensor::{extension::{EmptyExtensionProvider, ExtensionProvider}, factory::EngineTensorFactory, sub_tensor_iter::EngineTensorSubTensorIterator, unit_iter::EngineTensorUnitIterator, EngineTensor}, unit::UnitCompatible}, helper::{Interval, Position, Shape, Slice, VarArray, VarArrayCompatible}};
+use crate::{engine::{tensor::{extension::{EmptyExtensionProvider, ExtensionProvider}, factory::EngineTensorFactory, sub_tensor_iter::EngineTensorSubTensorIterator, unit_iter::EngineTensorUnitIterator, EngineTensor}, unit::UnitCompatible, EngineError}, helper::{Interval, Position, Shape, Slice, VarArray, VarArrayCompatible}};
 
 use super::array::Array;
 
@@ -190,6 +190,16 @@ impl<T: AllowedPadded> EngineTensor for Padded<T> {
         })
     }
 
+    //Padding doesn't survive expanding dimensions so a deep copy is made first
+    fn broadcast_to(&self, shape: &Shape) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        self.mat().broadcast_to(shape)
+    }
+
+    //Padding is positional so reordering the dimensions needs a deep copy first
+    fn permute(&self, dims: &[usize]) -> Result<Box<dyn EngineTensor<Unit = Self::Unit>>, EngineError> {
+        self.mat().permute(dims)
+    }
+
     fn extensions(&self)-> Box<dyn ExtensionProvider + '_> {
         Box::new(EmptyExtensionProvider::from(self))
     }
@@ -1,4 +1,6 @@
-use crate::{engine::EngineError, helper::{Shape, VarArrayCompatible}};
+use std::iter;
+
+use crate::{engine::{unit::UnitCompatible, ConvParams, EngineError}, helper::{Shape, VarArrayCompatible}};
 
 pub fn return_if_matched_shape<T>(a: &Shape, b: &Shape, out: T) -> Result<T, EngineError> {
     if a == b {
@@ -28,6 +30,23 @@ pub fn err_if_too_few_dimensions(a: &Shape, min_dims: usize) -> Result<(), Engin
     }
 }
 
+pub fn err_if_invalid_probability(p: f64) -> Result<(), EngineError> {
+    if (0.0..=1.0).contains(&p) {
+        Ok(())
+    } else {
+        Err(EngineError::InvalidProbability(p))
+    }
+}
+
+//A plain cast would wrap negative units and truncate fractional ones into a valid looking index
+pub fn unit_to_index<T: UnitCompatible>(unit: T) -> Result<usize, EngineError> {
+    if unit < T::zero() || unit.floor() != unit {
+        Err(EngineError::InvalidIndexUnit(format!("{unit:?}")))
+    } else {
+        Ok(unit.to())
+    }
+}
+
 pub fn err_if_dimension_mismatch(provided_dim: usize, expected_dim: usize) -> Result<(), EngineError> {
     if provided_dim == expected_dim {
         Ok(())
@@ -42,4 +61,303 @@ pub fn err_if_dimensions_mistmatch(provided_dims: &[usize], expected_dims: &[usi
     } else {
         Err(EngineError::DimensionsMismatch(provided_dims.into(), expected_dims.into()))
     }
-}
\ No newline at end of file
+}
+//Marks which axes of the shape are reduced, no axes means every axis
+pub fn reduced_axes(a: &Shape, axes: &[usize]) -> Result<Box<[bool]>, EngineError> {
+    let a_dims = a.len();
+
+    if let Some(axis) = axes.iter().find(|axis| **axis >= a_dims) {
+        return Err(EngineError::IndexOutOfRange(*axis, a_dims));
+    }
+
+    Ok((0..a_dims).map(|i| axes.is_empty() || axes.contains(&i)).collect())
+}
+
+pub fn reduced_axes_shape(a: &Shape, axes: &[usize], keep_dims: bool) -> Result<Shape, EngineError> {
+    let reduced = reduced_axes(a, axes)?;
+
+    if keep_dims {
+        Ok(Shape::from_iter(a.iter().zip(reduced.iter()).map(|(d, r)| if *r { 1 } else { d })))
+    } else {
+        let dims = a.iter().zip(reduced.iter()).filter_map(|(d, r)| if *r { None } else { Some(d) }).collect::<Vec<_>>();
+
+        if dims.is_empty() {
+            Ok(Shape::from([1].as_slice()))
+        } else {
+            Ok(Shape::from(dims.as_slice()))
+        }
+    }
+}
+
+pub fn err_if_zero_window(kernel: usize, stride: usize, dilation: usize) -> Result<(), EngineError> {
+    if kernel == 0 || stride == 0 || dilation == 0 {
+        Err(EngineError::ZeroWindow(kernel, stride, dilation))
+    } else {
+        Ok(())
+    }
+}
+
+//Number of windows along a dimension with the window's start moving by stride
+pub fn window_output_dim(dim: usize, kernel: usize, stride: usize, padding: usize, dilation: usize) -> Result<usize, EngineError> {
+    err_if_zero_window(kernel, stride, dilation)?;
+
+    let window = dilation * (kernel - 1) + 1;
+    let padded_dim = dim + 2 * padding;
+
+    if window > padded_dim {
+        Err(EngineError::WindowTooLarge(window, padded_dim))
+    } else {
+        Ok((padded_dim - window) / stride + 1)
+    }
+}
+
+pub fn pool_output_dim(dim: usize, kernel: usize, stride: usize, padding: usize, dilation: usize) -> Result<usize, EngineError> {
+    if padding * 2 > kernel {
+        return Err(EngineError::PaddingTooLarge(padding, kernel));
+    }
+
+    window_output_dim(dim, kernel, stride, padding, dilation)
+}
+
+//a: (batches, channels, y, x)
+pub fn pool_2d_shape(a: &Shape, kernel: (usize, usize), stride: (usize, usize), padding: (usize, usize), dilation: (usize, usize)) -> Result<Shape, EngineError> {
+    err_if_incorrect_num_dimensions(a, 4)?;
+
+    let out_y = pool_output_dim(a.get(2)?, kernel.0, stride.0, padding.0, dilation.0)?;
+    let out_x = pool_output_dim(a.get(3)?, kernel.1, stride.1, padding.1, dilation.1)?;
+
+    Ok(Shape::from([a.get(0)?, a.get(1)?, out_y, out_x].as_slice()))
+}
+
+//a: (batches, channels, y, x)
+pub fn adaptive_pool_2d_shape(a: &Shape, output_size: (usize, usize)) -> Result<Shape, EngineError> {
+    err_if_incorrect_num_dimensions(a, 4)?;
+
+    Ok(Shape::from([a.get(0)?, a.get(1)?, output_size.0, output_size.1].as_slice()))
+}
+
+//a: (batches, in_channels, *spatial)
+//kernel: (out_channels, in_channels / groups, *kernel_spatial)
+//out: (batches, out_channels, *out_spatial)
+pub fn conv_shape<const N: usize>(a: &Shape, kernel: &Shape, params: &ConvParams<N>) -> Result<Shape, EngineError> {
+    let ConvParams { stride, padding, dilation, groups } = *params;
+    let spatial_dims = N;
+
+    err_if_incorrect_num_dimensions(a, spatial_dims + 2)?;
+    err_if_incorrect_num_dimensions(kernel, spatial_dims + 2)?;
+
+    let in_channels = a.get(1)?;
+    let out_channels = kernel.get(0)?;
+
+    if groups == 0 || in_channels % groups != 0 {
+        return Err(EngineError::GroupsMismatch(groups, in_channels));
+    }
+
+    if out_channels % groups != 0 {
+        return Err(EngineError::GroupsMismatch(groups, out_channels));
+    }
+
+    err_if_dimension_mismatch(kernel.get(1)?, in_channels / groups)?;
+
+    let out_spatial = (0..spatial_dims).map(|i| {
+        window_output_dim(a.get(i + 2)?, kernel.get(i + 2)?, stride[i], padding[i], dilation[i])
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Shape::from_iter([a.get(0)?, out_channels].into_iter().chain(out_spatial)))
+}
+
+//a: (batches, in_channels, *spatial)
+//kernel: (in_channels, out_channels / groups, *kernel_spatial)
+//out: (batches, out_channels, *out_spatial)
+pub fn conv_transpose_shape<const N: usize>(a: &Shape, kernel: &Shape, params: &ConvParams<N>, output_padding: &[usize; N]) -> Result<Shape, EngineError> {
+    let ConvParams { stride, padding, dilation, groups } = *params;
+    let spatial_dims = N;
+
+    err_if_incorrect_num_dimensions(a, spatial_dims + 2)?;
+    err_if_incorrect_num_dimensions(kernel, spatial_dims + 2)?;
+
+    let in_channels = a.get(1)?;
+
+    if groups == 0 || in_channels % groups != 0 {
+        return Err(EngineError::GroupsMismatch(groups, in_channels));
+    }
+
+    err_if_dimension_mismatch(kernel.get(0)?, in_channels)?;
+
+    let out_spatial = (0..spatial_dims).map(|i| {
+        //Output padding only resolves which of the possible output sizes is wanted
+        err_if_zero_window(kernel.get(i + 2)?, stride[i], dilation[i])?;
+
+        if output_padding[i] >= stride[i].max(dilation[i]) {
+            return Err(EngineError::PaddingTooLarge(output_padding[i], stride[i].max(dilation[i])));
+        }
+
+        let full = (a.get(i + 2)? - 1) * stride[i] + dilation[i] * (kernel.get(i + 2)? - 1) + output_padding[i] + 1;
+
+        full.checked_sub(2 * padding[i]).filter(|d| *d > 0).ok_or(EngineError::WindowTooLarge(2 * padding[i], full))
+    }).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Shape::from_iter([a.get(0)?, kernel.get(1)? * groups].into_iter().chain(out_spatial)))
+}
+
+//Every shape must match the first apart from along axis, which is summed
+pub fn concat_shape(shapes: &[&Shape], axis: usize) -> Result<Shape, EngineError> {
+    let first = shapes.first().ok_or(EngineError::NoTensors())?;
+    err_if_too_few_dimensions(first, axis + 1)?;
+
+    let mut joined = 0;
+
+    for shape in shapes {
+        err_if_incorrect_num_dimensions(shape, first.len())?;
+
+        let dim = shape.get(axis)?;
+        let expected = Shape::from_iter(first.iter().enumerate().map(|(i, d)| if i == axis { dim } else { d }));
+        return_if_matched_shape(shape, &expected, ())?;
+
+        joined += dim;
+    }
+
+    Ok(Shape::from_iter(first.iter().enumerate().map(|(i, d)| if i == axis { joined } else { d })))
+}
+
+//Every shape must match, a new dimension is inserted at axis
+pub fn stack_shape(shapes: &[&Shape], axis: usize) -> Result<Shape, EngineError> {
+    let first = shapes.first().ok_or(EngineError::NoTensors())?;
+    err_if_too_few_dimensions(first, axis)?;
+
+    for shape in shapes {
+        return_if_matched_shape(shape, first, ())?;
+    }
+
+    Ok(Shape::from_iter(first.iter().take(axis).chain(iter::once(shapes.len())).chain(first.iter().skip(axis))))
+}
+
+pub fn permute_shape(a: &Shape, dims: &[usize]) -> Result<Shape, EngineError> {
+    let num_dims = a.len();
+
+    if dims.len() == num_dims && (0..num_dims).all(|d| dims.contains(&d)) {
+        Ok(Shape::from_iter(dims.iter().map(|d| a.get(*d).unwrap())))
+    } else {
+        Err(EngineError::InvalidPermutation(dims.into(), num_dims))
+    }
+}
+
+pub fn reshape_shape(a: &Shape, shape: &Shape) -> Result<Shape, EngineError> {
+    if a.elements() == shape.elements() {
+        Ok(shape.clone())
+    } else {
+        Err(EngineError::ShapeMismatch(a.clone(), shape.clone()))
+    }
+}
+
+pub fn flatten_shape(a: &Shape, start: usize, end: usize) -> Result<Shape, EngineError> {
+    if end >= a.len() {
+        return Err(EngineError::IndexOutOfRange(end, a.len()));
+    }
+
+    if start > end {
+        return Err(EngineError::IndexOutOfRange(start, end + 1));
+    }
+
+    let merged = a.iter().skip(start).take(end + 1 - start).product::<usize>();
+
+    Ok(Shape::from_iter(a.iter().take(start).chain([merged]).chain(a.iter().skip(end + 1))))
+}
+
+pub fn unflatten_shape(a: &Shape, axis: usize, sizes: &[usize]) -> Result<Shape, EngineError> {
+    err_if_dimension_mismatch(sizes.iter().product::<usize>(), a.get(axis)?)?;
+
+    Ok(Shape::from_iter(a.iter().take(axis).chain(sizes.iter().copied()).chain(a.iter().skip(axis + 1))))
+}
+
+pub fn narrow_shape(a: &Shape, axis: usize, start: usize, length: usize) -> Result<Shape, EngineError> {
+    let dim = a.get(axis)?;
+
+    if start + length > dim {
+        return Err(EngineError::IndexOutOfRange(start + length, dim));
+    }
+
+    Ok(Shape::from_iter(a.iter().enumerate().map(|(d, size)| if d == axis { length } else { size })))
+}
+
+//Permutation that swaps dim0 and dim1
+pub fn transpose_dims(a: &Shape, dim0: usize, dim1: usize) -> Result<Box<[usize]>, EngineError> {
+    let num_dims = a.len();
+
+    for dim in [dim0, dim1] {
+        if dim >= num_dims {
+            return Err(EngineError::IndexOutOfRange(dim, num_dims));
+        }
+    }
+
+    let mut dims = (0..num_dims).collect::<Box<[usize]>>();
+    dims.swap(dim0, dim1);
+
+    Ok(dims)
+}
+
+//index must have as many dimensions as a and fit inside it apart from along axis
+pub fn err_if_invalid_index_shape(a: &Shape, axis: usize, index: &Shape) -> Result<(), EngineError> {
+    err_if_too_few_dimensions(a, axis + 1)?;
+    err_if_incorrect_num_dimensions(index, a.len())?;
+
+    for (i, (index_dim, a_dim)) in index.iter().zip(a.iter()).enumerate() {
+        if i != axis && index_dim > a_dim {
+            return Err(EngineError::DimensionMismatch(index_dim, a_dim));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn index_select_shape(a: &Shape, axis: usize, indices: &Shape) -> Result<Shape, EngineError> {
+    err_if_too_few_dimensions(a, axis + 1)?;
+    err_if_incorrect_num_dimensions(indices, 1)?;
+
+    Ok(Shape::from_iter(a.iter().enumerate().map(|(i, d)| if i == axis { indices.elements() } else { d })))
+}
+
+pub fn embedding_shape(weight: &Shape, ids: &Shape) -> Result<Shape, EngineError> {
+    err_if_incorrect_num_dimensions(weight, 2)?;
+
+    Ok(Shape::from_iter(ids.iter().chain([weight.get(1)?])))
+}
+
+pub fn err_if_invalid_padding_idx(padding_idx: Option<usize>, num_embeddings: usize) -> Result<(), EngineError> {
+    match padding_idx {
+        Some(padding_idx) if padding_idx >= num_embeddings => Err(EngineError::IndexOutOfRange(padding_idx, num_embeddings)),
+        _ => Ok(()),
+    }
+}
+
+pub fn embedding_bag_shape(weight: &Shape, ids: &Shape) -> Result<Shape, EngineError> {
+    err_if_incorrect_num_dimensions(weight, 2)?;
+    err_if_incorrect_num_dimensions(ids, 2)?;
+
+    Ok(Shape::from_iter([ids.get(0)?, weight.get(1)?]))
+}
+
+//The trailing axes of a spanned by normalized_shape, which can't be empty
+pub fn normalized_axes(a: &Shape, normalized_shape: &Shape) -> Result<Vec<usize>, EngineError> {
+    let skip = a.len().checked_sub(normalized_shape.len()).filter(|skip| normalized_shape.len() != 0 && a.iter().skip(*skip).eq(normalized_shape.iter()));
+
+    if let Some(skip) = skip {
+        Ok((skip..a.len()).collect())
+    } else {
+        Err(EngineError::ShapeMismatch(a.clone(), normalized_shape.clone()))
+    }
+}
+
+pub fn attention_shape(q: &Shape, k: &Shape, v: &Shape) -> Result<Shape, EngineError> {
+    err_if_too_few_dimensions(q, 2)?;
+    err_if_too_few_dimensions(k, 2)?;
+    err_if_too_few_dimensions(v, 2)?;
+
+    err_if_dimension_mismatch(k.get(k.len() - 1)?, q.get(q.len() - 1)?)?;
+    err_if_dimension_mismatch(v.get(v.len() - 2)?, k.get(k.len() - 2)?)?;
+
+    //The batch dimensions of the tensors with fewer dimensions are broadcast like matmul
+    let batches = [q, k, v].into_iter().max_by_key(|s| s.len()).unwrap();
+
+    Ok(Shape::from_iter(batches.iter().take(batches.len() - 2).chain([q.get(q.len() - 2)?, v.get(v.len() - 1)?])))
+}
@@ -2,11 +2,13 @@ mod inferred_shape;
 mod shape;
 mod stride;
 mod position;
+mod rng;
 pub mod slice;
 mod vararray;
 
 pub use shape::*;
 pub use stride::*;
 pub use position::*;
+pub use rng::*;
 pub use slice::*;
 pub use vararray::*;
\ No newline at end of file
@@ -88,6 +88,7 @@ macro_rules! position {
         Position::from([$($x),+].as_slice())
     };
 }
+#[allow(unused_imports)]
 pub(crate) use position;
 
 #[derive(Error, Debug)]
@@ -0,0 +1,122 @@
+//xoshiro256** seeded through splitmix64, the same seed always gives the same stream on every platform
+//Not suitable for anything cryptographic
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        //splitmix64 spreads the seed over the whole state so it can never be all zeros
+        let mut x = seed;
+        let mut splitmix = || {
+            x = x.wrapping_add(0x9e3779b97f4a7c15);
+
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+            z ^ (z >> 31)
+        };
+
+        Self {
+            state: [splitmix(), splitmix(), splitmix(), splitmix()],
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+
+        let t = s[1] << 17;
+
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    //Uniform in [0, 1) using the top 53 bits
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    pub fn uniform(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+
+    //Box-Muller, only one of the pair is used so every call takes the same number of steps
+    pub fn normal(&mut self, mean: f64, std: f64) -> f64 {
+        //1 - u is in (0, 1] so the log is finite
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+
+        mean + std * (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    pub fn bernoulli(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    //Uniform in [0, n) without modulo bias, n must be more than zero
+    pub fn below(&mut self, n: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % n;
+
+        loop {
+            let x = self.next_u64();
+
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    //Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reference_stream() {
+        //From the reference implementation's state of [1, 2, 3, 4]
+        let mut rng = Rng { state: [1, 2, 3, 4] };
+
+        assert_eq!([rng.next_u64(), rng.next_u64(), rng.next_u64(), rng.next_u64()], [11520, 0, 1509978240, 1215971899390074240]);
+    }
+
+    #[test]
+    fn seeded() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+
+        let mut rng = Rng::new(7);
+
+        assert!((0..1000).map(|_| rng.next_f64()).all(|x| (0.0..1.0).contains(&x)));
+        assert!((0..1000).map(|_| rng.below(3)).all(|x| x < 3));
+
+        let mut items = (0..10).collect::<Vec<_>>();
+        rng.shuffle(&mut items);
+
+        let mut sorted = items.clone();
+        sorted.sort();
+
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+}
@@ -25,6 +25,31 @@ impl Shape {
     pub fn last(&self) -> Position {
         Position::new(self.iter().map(|x| x - 1).collect())
     }
+
+    //NumPy broadcasting, trailing dimensions are aligned and missing dimensions are treated as 1
+    //Dimensions must either match or one of them must be 1
+    pub fn broadcast_with(&self, other: &Shape) -> Option<Shape> {
+        let dims = self.len().max(other.len());
+
+        let aligned_dim = |shape: &Shape, i: usize| {
+            let missing = dims - shape.len();
+
+            if i < missing { 1 } else { shape.get(i - missing).unwrap() }
+        };
+
+        (0..dims).map(|i| {
+            let a_dim = aligned_dim(self, i);
+            let b_dim = aligned_dim(other, i);
+
+            if a_dim == b_dim || b_dim == 1 {
+                Some(a_dim)
+            } else if a_dim == 1 {
+                Some(b_dim)
+            } else {
+                None
+            }
+        }).collect::<Option<Shape>>()
+    }
 }
 
 impl VarArrayCompatible for Shape {
@@ -71,3 +96,16 @@ macro_rules! shape {
 }
 pub(crate) use shape;
 
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn broadcast_with() {
+        assert_eq!(shape![4, 3].broadcast_with(&shape![1]), Some(shape![4, 3]));
+        assert_eq!(shape![2, 1, 3].broadcast_with(&shape![5, 1]), Some(shape![2, 5, 3]));
+        assert_eq!(shape![1, 4].broadcast_with(&shape![3, 1]), Some(shape![3, 4]));
+        assert_eq!(shape![4, 3].broadcast_with(&shape![4]), None);
+    }
+}
@@ -93,7 +93,8 @@ impl Interval {
         let finish_index = self.end_index(dim);
         let step_index = self.step();
 
-        (finish_index - start_index) / step_index
+        //Any remainder still holds a unit at the start of the last step
+        (finish_index - start_index).div_ceil(step_index)
     }
 
 
@@ -199,4 +200,15 @@ mod test {
         assert_eq!(i5.step(), 1);
         assert_eq!(i5.len(dim), dim);       
     }
+
+    #[test]
+    fn interval_len_partial_step() {
+        let dim = 8usize;
+
+        //A step that does not divide the range still takes the unit at its start
+        assert_eq!(Interval::between_with_step(0, 5, 2).len(dim), 3);
+        assert_eq!(Interval::between_with_step(1, 8, 3).len(dim), 3);
+        assert_eq!(Interval::between_with_step(0, 6, 2).len(dim), 3);
+        assert_eq!(Interval::new(None, None, Some(3)).len(dim), 3);
+    }
 }
@@ -9,6 +9,8 @@ mod engine;
 mod helper;
 mod comp_graph;
 mod engine_impl;
+mod nn;
+mod optim;
 
 fn main() {
     let mut graph = CompGraph::<f64>::new();
@@ -16,11 +18,10 @@ fn main() {
     let a = graph.create_root(Box::new(Array::from_slice([1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12.].as_slice(), Shape::from([4, 3].as_slice()))));
     let b = graph.create_root(Box::new(Array::from_slice([2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12., 13.].as_slice(), Shape::from([4, 3].as_slice()))));
 
-    let mut c = a;
-    let divider = graph.create_root(Array::from_slice(&[0.99], shape![1]).generic().broadcast_splice(0, &[4, 3]).reshape(&shape![4, 3]).mat());
-    for _ in 0..100000 {
-        c = graph.div::<Basic, Array<_>>(&c, &divider);
-    }
+    let divider = graph.create_root(Array::from_slice(&[0.99], shape![1]).generic());
+    let c = graph.scan(&a, &[divider], 100000, |body, carry, captures| {
+        body.div::<Basic, Array<_>>(carry, &captures[0])
+    }).unwrap();
 
     graph.non_populating_eval(&c).unwrap();
 
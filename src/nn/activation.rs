@@ -0,0 +1,49 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::factory::EngineTensorFactory, unit::UnitCompatible, Engine}};
+
+use super::Layer;
+
+pub struct Relu<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Relu<T, E, F> {
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for Relu<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        graph.relu::<E, F>(input)
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        vec![]
+    }
+}
+
+pub struct Sigmoid<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Sigmoid<T, E, F> {
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for Sigmoid<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        graph.sigmoid::<E, F>(input)
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        vec![]
+    }
+}
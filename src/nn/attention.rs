@@ -0,0 +1,246 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor, ComputationGraphError}, engine::{tensor::factory::EngineTensorFactory, unit::UnitCompatible, Engine, EngineError}, engine_impl::util::err_if_dimension_mismatch, helper::VarArrayCompatible};
+
+use super::{linear::Linear, Layer};
+
+//Keys and values of the previous steps, already projected and split into heads: (batches, num_heads, seq_len, head_dim)
+pub struct KvCache {
+    keys: Option<CompGraphTensor>,
+    values: Option<CompGraphTensor>,
+
+    len: usize,
+}
+
+impl KvCache {
+    pub fn new() -> Self {
+        Self {
+            keys: None,
+            values: None,
+
+            len: 0,
+        }
+    }
+
+    pub fn keys(&self) -> Option<&CompGraphTensor> {
+        self.keys.as_ref()
+    }
+
+    pub fn values(&self) -> Option<&CompGraphTensor> {
+        self.values.as_ref()
+    }
+
+    //Number of cached steps
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys = None;
+        self.values = None;
+        self.len = 0;
+    }
+}
+
+//query: (batches, target_len, embed_dim), key and value: (batches, source_len, *)
+//out: (batches, target_len, embed_dim)
+//Each head attends with its own embed_dim / num_heads features of the projections
+pub struct MultiheadAttention<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    q_proj: Linear<T, E, F>,
+    k_proj: Linear<T, E, F>,
+    v_proj: Linear<T, E, F>,
+    out_proj: Linear<T, E, F>,
+
+    embed_dim: usize,
+    num_heads: usize,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> MultiheadAttention<T, E, F> {
+    //The query, key and value projections must give embed_dim features, which out_proj maps back to embed_dim
+    pub fn new(q_proj: Linear<T, E, F>, k_proj: Linear<T, E, F>, v_proj: Linear<T, E, F>, out_proj: Linear<T, E, F>, num_heads: usize) -> Result<Self, EngineError> {
+        let embed_dim = q_proj.out_features();
+
+        err_if_dimension_mismatch(k_proj.out_features(), embed_dim)?;
+        err_if_dimension_mismatch(v_proj.out_features(), embed_dim)?;
+        err_if_dimension_mismatch(out_proj.in_features(), embed_dim)?;
+        err_if_dimension_mismatch(out_proj.out_features(), embed_dim)?;
+
+        if num_heads == 0 || embed_dim % num_heads != 0 {
+            return Err(EngineError::GroupsMismatch(num_heads, embed_dim));
+        }
+
+        Ok(Self {
+            q_proj,
+            k_proj,
+            v_proj,
+            out_proj,
+
+            embed_dim,
+            num_heads,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn embed_dim(&self) -> usize {
+        self.embed_dim
+    }
+
+    pub fn num_heads(&self) -> usize {
+        self.num_heads
+    }
+
+    pub fn head_dim(&self) -> usize {
+        self.embed_dim / self.num_heads
+    }
+
+    //mask broadcasts to (batches, num_heads, target_len, source_len), see Engine::scaled_dot_product_attention
+    //causal masks like PyTorch's is_causal, query i only attends to keys up to i
+    pub fn attend(&self, graph: &mut CompGraph<T>, query: &CompGraphTensor, key: &CompGraphTensor, value: &CompGraphTensor, mask: Option<&CompGraphTensor>, causal: bool) -> CompGraphTensor {
+        let q = self.project_heads(graph, &self.q_proj, query);
+        let k = self.project_heads(graph, &self.k_proj, key);
+        let v = self.project_heads(graph, &self.v_proj, value);
+
+        self.combine_heads(graph, &q, &k, &v, mask, causal.then_some(0))
+    }
+
+    //Causal self attention of the new steps in input over themselves and every step held by the cache
+    //The projected keys and values of input are appended to the cache, the shape of input has to be inferable
+    pub fn attend_cached(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, cache: &mut KvCache) -> Result<CompGraphTensor, ComputationGraphError> {
+        let steps = graph.shape(input)?.get(1).map_err(EngineError::from)?;
+
+        let q = self.project_heads(graph, &self.q_proj, input);
+        let mut k = self.project_heads(graph, &self.k_proj, input);
+        let mut v = self.project_heads(graph, &self.v_proj, input);
+
+        if let (Some(cached_k), Some(cached_v)) = (&cache.keys, &cache.values) {
+            k = graph.concat::<E, F>(&[cached_k, &k], 2);
+            v = graph.concat::<E, F>(&[cached_v, &v], 2);
+        }
+
+        //The new steps come after every cached one, so each query also sees the cached keys
+        let offset = cache.len;
+
+        cache.keys = Some(k.clone());
+        cache.values = Some(v.clone());
+        cache.len += steps;
+
+        Ok(self.combine_heads(graph, &q, &k, &v, None, Some(offset)))
+    }
+
+    //(batches, seq_len, embed_dim) -> (batches, num_heads, seq_len, head_dim)
+    fn project_heads(&self, graph: &mut CompGraph<T>, proj: &Linear<T, E, F>, input: &CompGraphTensor) -> CompGraphTensor {
+        let projected = proj.forward(graph, input);
+        let split = graph.unflatten::<E>(&projected, 2, &[self.num_heads, self.head_dim()]);
+
+        graph.permute::<E>(&split, &[0, 2, 1, 3])
+    }
+
+    fn combine_heads(&self, graph: &mut CompGraph<T>, q: &CompGraphTensor, k: &CompGraphTensor, v: &CompGraphTensor, mask: Option<&CompGraphTensor>, causal_offset: Option<usize>) -> CompGraphTensor {
+        let heads = graph.scaled_dot_product_attention::<E, F>(q, k, v, mask, causal_offset);
+
+        let merged = graph.permute::<E>(&heads, &[0, 2, 1, 3]);
+        let merged = graph.flatten::<E>(&merged, 2, 3);
+
+        self.out_proj.forward(graph, &merged)
+    }
+}
+
+//Self attention without a mask
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for MultiheadAttention<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        self.attend(graph, input, input, input, None, false)
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        [&self.q_proj, &self.k_proj, &self.v_proj, &self.out_proj].into_iter().flat_map(|proj| proj.parameters()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine::tensor::factory::EngineTensorFactory, engine_impl::{basic::Basic, tensor::array::Array}, helper::{shape, Shape}};
+
+    use super::*;
+
+    fn projection(graph: &mut CompGraph<f64>, weight: &[f64]) -> Linear<f64, Basic, Array<f64>> {
+        Linear::new(graph, Array::from_slice(weight, shape![4, 4]).generic(), None).unwrap()
+    }
+
+    fn attention(graph: &mut CompGraph<f64>) -> MultiheadAttention<f64, Basic, Array<f64>> {
+        let identity = [1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.];
+        let mixed = [0.5, -1., 0., 0.25, 1., 0., 0.5, 0., 0., 0.25, 1., -0.5, -0.5, 0., 0., 1.];
+
+        let q_proj = projection(graph, &mixed);
+        let k_proj = projection(graph, &identity);
+        let v_proj = projection(graph, &mixed);
+        let out_proj = projection(graph, &identity);
+
+        MultiheadAttention::new(q_proj, k_proj, v_proj, out_proj, 2).unwrap()
+    }
+
+    #[test]
+    fn cached_matches_causal() {
+        let mut graph = CompGraph::<f64>::new();
+        let mha = attention(&mut graph);
+
+        assert_eq!(mha.parameters().len(), 4);
+
+        let steps = [[0.1, 0.2, -0.3, 0.4], [1., -0.5, 0.25, 0.], [-0.2, 0.3, 0.7, -1.]];
+        let input = graph.create_root(Array::from_slice(steps.concat().as_slice(), shape![1, 3, 4]).generic());
+
+        let full = mha.attend(&mut graph, &input, &input, &input, None, true);
+        graph.non_populating_eval(&full).unwrap();
+        assert_eq!(graph.shape(&full).unwrap(), shape![1, 3, 4]);
+
+        let full = graph.iter(&full).collect::<Vec<_>>();
+
+        //Decoding one step at a time has to give the same rows as attending to every step at once
+        let mut cache = KvCache::new();
+
+        for (i, step) in steps.iter().enumerate() {
+            let input = graph.create_root(Array::from_slice(step, shape![1, 1, 4]).generic());
+
+            let out = mha.attend_cached(&mut graph, &input, &mut cache).unwrap();
+            graph.non_populating_eval(&out).unwrap();
+
+            for (res_element, expected_element) in graph.iter(&out).zip(&full[(i * 4)..((i + 1) * 4)]) {
+                assert!((res_element - expected_element).abs() < 1e-12, "{res_element} != {expected_element}");
+            }
+        }
+
+        assert_eq!(cache.len(), 3);
+
+        //Several steps at once are offset by the cached steps, so still line up with the full rows
+        cache.clear();
+
+        let first = graph.create_root(Array::from_slice(steps[..2].concat().as_slice(), shape![1, 2, 4]).generic());
+        let second = graph.create_root(Array::from_slice(&steps[2], shape![1, 1, 4]).generic());
+
+        for (input, rows) in [(first, 0..2), (second, 2..3)] {
+            let out = mha.attend_cached(&mut graph, &input, &mut cache).unwrap();
+            graph.non_populating_eval(&out).unwrap();
+
+            for (res_element, expected_element) in graph.iter(&out).zip(&full[(rows.start * 4)..(rows.end * 4)]) {
+                assert!((res_element - expected_element).abs() < 1e-12, "{res_element} != {expected_element}");
+            }
+        }
+    }
+
+    #[test]
+    fn heads_must_divide_embed_dim() {
+        let mut graph = CompGraph::<f64>::new();
+        let identity = [1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.];
+
+        let projections = [(); 4].map(|_| projection(&mut graph, &identity));
+        let [q_proj, k_proj, v_proj, out_proj] = projections;
+
+        assert!(MultiheadAttention::new(q_proj, k_proj, v_proj, out_proj, 3).is_err());
+    }
+}
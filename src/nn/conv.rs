@@ -0,0 +1,54 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible, ConvParams, Engine, EngineError}, engine_impl::util::{err_if_incorrect_num_dimensions, return_if_matched_shape}, helper::{shape, Shape, VarArrayCompatible}};
+
+use super::Layer;
+
+//input: (batches, in_channels, y, x)
+//kernel: (out_channels, in_channels / groups, k_y, k_x), bias: (out_channels)
+//out: (batches, out_channels, out_y, out_x)
+pub struct Conv2d<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    kernel: CompGraphTensor,
+    bias: Option<CompGraphTensor>,
+
+    params: ConvParams<2>,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Conv2d<T, E, F> {
+    pub fn new(graph: &mut CompGraph<T>, kernel: Box<dyn EngineTensor<Unit = T>>, bias: Option<Box<dyn EngineTensor<Unit = T>>>, params: ConvParams<2>) -> Result<Self, EngineError> {
+        err_if_incorrect_num_dimensions(kernel.shape(), 4)?;
+
+        if let Some(bias) = &bias {
+            return_if_matched_shape(bias.shape(), &shape![kernel.shape().get(0)?], ())?;
+        }
+
+        Ok(Self {
+            kernel: graph.create_root(kernel),
+            bias: bias.map(|bias| graph.create_root(bias)),
+
+            params,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn kernel(&self) -> &CompGraphTensor {
+        &self.kernel
+    }
+
+    pub fn bias(&self) -> Option<&CompGraphTensor> {
+        self.bias.as_ref()
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for Conv2d<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        graph.conv2d::<E, F>(input, &self.kernel, self.bias.as_ref(), self.params)
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        [self.kernel.clone()].into_iter().chain(self.bias.clone()).collect()
+    }
+}
@@ -0,0 +1,96 @@
+use std::{cell::RefCell, marker::PhantomData};
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::factory::EngineTensorFactory, unit::UnitCompatible, Engine, EngineError}, engine_impl::util::err_if_invalid_probability, helper::Rng};
+
+use super::Layer;
+
+//Each forward while training seeds its node from the layer's rng, and the node draws a new mask every time it's evaluated
+//Evaluation passes the input through unchanged
+pub struct Dropout<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    p: f64,
+    training: bool,
+
+    //Layer::forward only borrows the layer
+    rng: RefCell<Rng>,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Dropout<T, E, F> {
+    //Starts in training mode
+    pub fn new(p: f64, rng: Rng) -> Result<Self, EngineError> {
+        err_if_invalid_probability(p)?;
+
+        Ok(Self {
+            p,
+            training: true,
+
+            rng: RefCell::new(rng),
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    pub fn train(&mut self) {
+        self.training = true;
+    }
+
+    pub fn eval(&mut self) {
+        self.training = false;
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for Dropout<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        graph.dropout::<E, F>(input, self.p, self.training, &mut self.rng.borrow_mut())
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine::tensor::factory::EngineTensorFactory, engine_impl::{basic::Basic, tensor::array::Array}, helper::{shape, Shape}};
+
+    use super::*;
+
+    #[test]
+    fn train_then_eval() {
+        let mut graph = CompGraph::<f64>::new();
+        let input = graph.create_root(Array::from_iter(std::iter::repeat(1.).take(64), shape![64]).generic());
+
+        let mut dropout = Dropout::<f64, Basic, Array<f64>>::new(0.5, Rng::new(3)).unwrap();
+
+        let first = dropout.forward(&mut graph, &input);
+        let second = dropout.forward(&mut graph, &input);
+
+        graph.non_populating_eval(&first).unwrap();
+        graph.non_populating_eval(&second).unwrap();
+
+        assert!(graph.iter(&first).all(|x| x == 0. || x == 2.));
+        assert!(!graph.iter(&first).eq(graph.iter(&second)));
+
+        //The same seed gives the same units
+        let again = Dropout::<f64, Basic, Array<f64>>::new(0.5, Rng::new(3)).unwrap().forward(&mut graph, &input);
+        graph.non_populating_eval(&again).unwrap();
+        assert!(graph.iter(&first).eq(graph.iter(&again)));
+
+        dropout.eval();
+
+        let out = dropout.forward(&mut graph, &input);
+        graph.non_populating_eval(&out).unwrap();
+        assert!(graph.iter(&out).all(|x| x == 1.));
+
+        assert!(Dropout::<f64, Basic, Array<f64>>::new(1.5, Rng::new(3)).is_err());
+    }
+}
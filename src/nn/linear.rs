@@ -0,0 +1,109 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible, Engine, EngineError}, engine_impl::util::{err_if_dimension_mismatch, err_if_incorrect_num_dimensions}, helper::VarArrayCompatible};
+
+use super::Layer;
+
+//input: (*, in_features) with at least one batch dimension, as it goes through matmul
+//weight: (out_features, in_features)
+//bias: (out_features)
+//out: (*, out_features)
+pub struct Linear<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    weight: CompGraphTensor,
+    bias: Option<CompGraphTensor>,
+
+    in_features: usize,
+    out_features: usize,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Linear<T, E, F> {
+    pub fn new(graph: &mut CompGraph<T>, weight: Box<dyn EngineTensor<Unit = T>>, bias: Option<Box<dyn EngineTensor<Unit = T>>>) -> Result<Self, EngineError> {
+        err_if_incorrect_num_dimensions(weight.shape(), 2)?;
+
+        let out_features = weight.shape().get(0)?;
+        let in_features = weight.shape().get(1)?;
+
+        if let Some(bias) = bias.as_ref() {
+            err_if_incorrect_num_dimensions(bias.shape(), 1)?;
+            err_if_dimension_mismatch(bias.shape().get(0)?, out_features)?;
+        }
+
+        Ok(Self {
+            weight: graph.create_root(weight),
+            bias: bias.map(|b| graph.create_root(b)),
+
+            in_features,
+            out_features,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn in_features(&self) -> usize {
+        self.in_features
+    }
+
+    pub fn out_features(&self) -> usize {
+        self.out_features
+    }
+
+    pub fn weight(&self) -> &CompGraphTensor {
+        &self.weight
+    }
+
+    pub fn bias(&self) -> Option<&CompGraphTensor> {
+        self.bias.as_ref()
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for Linear<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        //input @ weight^T, with the bias broadcast over every row
+        let weight_t = graph.transpose::<E>(&self.weight, 0, 1);
+        let out = graph.matmul::<E, F>(input, &weight_t);
+
+        match self.bias.as_ref() {
+            Some(bias) => graph.add::<E, F>(&out, bias),
+            None => out,
+        }
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        [self.weight.clone()].into_iter().chain(self.bias.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine_impl::{basic::Basic, tensor::array::Array}, helper::{shape, Shape}};
+
+    use super::*;
+
+    #[test]
+    fn forward() {
+        let mut graph = CompGraph::<f64>::new();
+
+        let weight = Array::from_slice(&[1., 0., 1., 0., 1., 0.], shape![2, 3]).generic();
+        let bias = Array::from_slice(&[0.5, -0.5], shape![2]).generic();
+
+        let linear = Linear::<f64, Basic, Array<f64>>::new(&mut graph, weight.clone(), Some(bias)).unwrap();
+        let unbiased = Linear::<f64, Basic, Array<f64>>::new(&mut graph, weight, None).unwrap();
+
+        //The leading dimensions are batches, the bias is added to every row
+        let input = graph.create_root(Array::from_slice(&[1., 2., 3., 4., 5., 6.], shape![2, 1, 3]).generic());
+
+        let out = linear.forward(&mut graph, &input);
+        let out_unbiased = unbiased.forward(&mut graph, &input);
+
+        graph.non_populating_eval(&out).unwrap();
+        graph.non_populating_eval(&out_unbiased).unwrap();
+
+        assert_eq!(graph.shape(&out).unwrap(), shape![2, 1, 2]);
+        assert_eq!(graph.iter(&out).collect::<Vec<_>>(), vec![4.5, 1.5, 10.5, 4.5]);
+        assert_eq!(graph.iter(&out_unbiased).collect::<Vec<_>>(), vec![4., 2., 10., 5.]);
+
+        assert!(Linear::<f64, Basic, Array<f64>>::new(&mut graph, Array::from_slice(&[1., 2.], shape![2]).generic(), None).is_err());
+    }
+}
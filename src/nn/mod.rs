@@ -0,0 +1,17 @@
+pub mod activation;
+pub mod attention;
+pub mod conv;
+pub mod dropout;
+pub mod linear;
+pub mod norm;
+pub mod recurrent;
+pub mod sequential;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::unit::UnitCompatible};
+
+//Layers own their parameters as roots in a graph and add nodes to that graph when applied
+//The engine and factory used for the added nodes are chosen when the layer is created
+pub trait Layer<T: UnitCompatible> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor;
+    fn parameters(&self) -> Vec<CompGraphTensor>;
+}
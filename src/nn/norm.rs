@@ -0,0 +1,143 @@
+use std::{iter, marker::PhantomData};
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor, ComputationGraphError}, engine::{tensor::factory::EngineTensorFactory, unit::UnitCompatible, Engine}, helper::{shape, Shape}};
+
+use super::Layer;
+
+//input: (batches, num_features, y, x)
+//Training normalises each feature using the statistics of the batch, evaluation uses the running statistics
+pub struct BatchNorm2d<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    weight: CompGraphTensor,
+    bias: CompGraphTensor,
+
+    running_mean: CompGraphTensor,
+    running_var: CompGraphTensor,
+
+    num_features: usize,
+    eps: f64,
+    momentum: f64,
+    training: bool,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> BatchNorm2d<T, E, F> {
+    //Weight and running variance start as ones, bias and running mean as zeros
+    //Starts in training mode
+    pub fn new(graph: &mut CompGraph<T>, num_features: usize, eps: f64, momentum: f64) -> Self {
+        let ones = || F::from_iter(iter::repeat(T::one()).take(num_features), shape![num_features]).generic();
+        let zeros = || F::from_iter(iter::repeat(T::zero()).take(num_features), shape![num_features]).generic();
+
+        Self {
+            weight: graph.create_root(ones()),
+            bias: graph.create_root(zeros()),
+
+            running_mean: graph.create_root(zeros()),
+            running_var: graph.create_root(ones()),
+
+            num_features,
+            eps,
+            momentum,
+            training: true,
+
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    pub fn weight(&self) -> &CompGraphTensor {
+        &self.weight
+    }
+
+    pub fn bias(&self) -> &CompGraphTensor {
+        &self.bias
+    }
+
+    pub fn running_mean(&self) -> &CompGraphTensor {
+        &self.running_mean
+    }
+
+    pub fn running_var(&self) -> &CompGraphTensor {
+        &self.running_var
+    }
+
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    pub fn train(&mut self) {
+        self.training = true;
+    }
+
+    pub fn eval(&mut self) {
+        self.training = false;
+    }
+
+    //Folds the statistics of the batch into the running statistics
+    //The input must be computable, the running statistic roots are replaced with the results
+    pub fn update_running_stats(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> Result<(), ComputationGraphError> {
+        let mean = graph.batch_norm_running_mean::<E, F>(input, &self.running_mean, self.momentum);
+        let var = graph.batch_norm_running_var::<E, F>(input, &self.running_var, self.momentum);
+
+        graph.non_populating_eval(&mean)?;
+        graph.non_populating_eval(&var)?;
+
+        let mean = graph.tensor(&mean)?.clone();
+        let var = graph.tensor(&var)?.clone();
+
+        graph.replace_root(&self.running_mean, mean)?;
+        graph.replace_root(&self.running_var, var)
+    }
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Layer<T> for BatchNorm2d<T, E, F> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        if self.training {
+            graph.batch_norm_no_running::<E, F>(input, &self.weight, &self.bias, self.eps)
+        } else {
+            graph.batch_norm_running::<E, F>(input, &self.running_mean, &self.running_var, &self.weight, &self.bias, self.eps)
+        }
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        vec![self.weight.clone(), self.bias.clone()]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine_impl::{basic::Basic, tensor::array::Array}, nn::Layer};
+
+    use super::*;
+
+    #[test]
+    fn train_then_eval() {
+        let mut graph = CompGraph::<f64>::new();
+
+        let mut norm = BatchNorm2d::<f64, Basic, Array<f64>>::new(&mut graph, 1, 0.0, 1.0);
+        let input = graph.create_root(Array::from_slice(&[1., 3., 5., 7.], shape![1, 1, 2, 2]).generic());
+
+        //Batch statistics don't depend on the running statistics
+        let out = norm.forward(&mut graph, &input);
+        graph.non_populating_eval(&out).unwrap();
+        assert!((graph.iter(&out).sum::<f64>()).abs() < 1e-12);
+
+        //Momentum of 1 replaces the running statistics with the batch's
+        norm.update_running_stats(&mut graph, &input).unwrap();
+        assert!(graph.iter(norm.running_mean()).eq([4.]));
+        assert!(graph.iter(norm.running_var()).all(|x| (x - 20. / 3.).abs() < 1e-12));
+
+        norm.eval();
+
+        let out = norm.forward(&mut graph, &input);
+        graph.non_populating_eval(&out).unwrap();
+
+        let std = (20. / 3_f64).sqrt();
+        for (res_element, expected_element) in graph.iter(&out).zip([-3. / std, -1. / std, 1. / std, 3. / std]) {
+            assert!((res_element - expected_element).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use std::{iter, marker::PhantomData};
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor, ComputationGraphError}, engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible, Engine, EngineError}, engine_impl::util::{err_if_dimension_mismatch, err_if_incorrect_num_dimensions}, helper::{shape, Shape, VarArrayCompatible}};
+
+use super::{linear::Linear, Layer};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Nonlinearity {
+    Tanh,
+    Relu,
+}
+
+//The projections of every gate stacked in PyTorch's order so exported weights can be used as is
+//weight_ih: (gates * hidden_size, input_size), weight_hh: (gates * hidden_size, hidden_size)
+//bias: (bias_ih, bias_hh), both (gates * hidden_size)
+struct GateWeights<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    ih: Linear<T, E, F>,
+    hh: Linear<T, E, F>,
+
+    hidden_size: usize,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> GateWeights<T, E, F> {
+    fn new(graph: &mut CompGraph<T>, gates: usize, weight_ih: Box<dyn EngineTensor<Unit = T>>, weight_hh: Box<dyn EngineTensor<Unit = T>>, bias: Option<(Box<dyn EngineTensor<Unit = T>>, Box<dyn EngineTensor<Unit = T>>)>) -> Result<Self, EngineError> {
+        err_if_incorrect_num_dimensions(weight_ih.shape(), 2)?;
+        err_if_incorrect_num_dimensions(weight_hh.shape(), 2)?;
+
+        let hidden_size = weight_hh.shape().get(1)?;
+
+        err_if_dimension_mismatch(weight_ih.shape().get(0)?, gates * hidden_size)?;
+        err_if_dimension_mismatch(weight_hh.shape().get(0)?, gates * hidden_size)?;
+
+        let (bias_ih, bias_hh) = bias.unzip();
+
+        Ok(Self {
+            ih: Linear::new(graph, weight_ih, bias_ih)?,
+            hh: Linear::new(graph, weight_hh, bias_hh)?,
+
+            hidden_size,
+        })
+    }
+
+    fn input_size(&self) -> usize {
+        self.ih.in_features()
+    }
+
+    //The units of a single gate from a stacked projection: (batches, hidden_size)
+    fn gate(&self, graph: &mut CompGraph<T>, projected: &CompGraphTensor, gate: usize) -> CompGraphTensor {
+        graph.narrow::<E>(projected, 1, gate * self.hidden_size, self.hidden_size)
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.ih.parameters().into_iter().chain(self.hh.parameters()).collect()
+    }
+}
+
+//input: (batches, input_size), hidden: (batches, hidden_size)
+//h' = nonlinearity(input @ weight_ih^T + bias_ih + hidden @ weight_hh^T + bias_hh)
+pub struct RnnCell<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    weights: GateWeights<T, E, F>,
+    nonlinearity: Nonlinearity,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> RnnCell<T, E, F> {
+    pub fn new(graph: &mut CompGraph<T>, weight_ih: Box<dyn EngineTensor<Unit = T>>, weight_hh: Box<dyn EngineTensor<Unit = T>>, bias: Option<(Box<dyn EngineTensor<Unit = T>>, Box<dyn EngineTensor<Unit = T>>)>, nonlinearity: Nonlinearity) -> Result<Self, EngineError> {
+        Ok(Self {
+            weights: GateWeights::new(graph, 1, weight_ih, weight_hh, bias)?,
+            nonlinearity,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.weights.input_size()
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.weights.hidden_size
+    }
+
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, hidden: &CompGraphTensor) -> CompGraphTensor {
+        let ih = self.weights.ih.forward(graph, input);
+        let hh = self.weights.hh.forward(graph, hidden);
+        let pre = graph.add::<E, F>(&ih, &hh);
+
+        match self.nonlinearity {
+            Nonlinearity::Tanh => graph.tanh::<E, F>(&pre),
+            Nonlinearity::Relu => graph.relu::<E, F>(&pre),
+        }
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.weights.parameters()
+    }
+}
+
+//input: (batches, input_size), hidden and cell: (batches, hidden_size)
+//Gates are ordered input, forget, cell, output
+pub struct LstmCell<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    weights: GateWeights<T, E, F>,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> LstmCell<T, E, F> {
+    pub fn new(graph: &mut CompGraph<T>, weight_ih: Box<dyn EngineTensor<Unit = T>>, weight_hh: Box<dyn EngineTensor<Unit = T>>, bias: Option<(Box<dyn EngineTensor<Unit = T>>, Box<dyn EngineTensor<Unit = T>>)>) -> Result<Self, EngineError> {
+        Ok(Self {
+            weights: GateWeights::new(graph, 4, weight_ih, weight_hh, bias)?,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.weights.input_size()
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.weights.hidden_size
+    }
+
+    //Returns the next (hidden, cell)
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, hidden: &CompGraphTensor, cell: &CompGraphTensor) -> (CompGraphTensor, CompGraphTensor) {
+        let ih = self.weights.ih.forward(graph, input);
+        let hh = self.weights.hh.forward(graph, hidden);
+        let gates = graph.add::<E, F>(&ih, &hh);
+
+        let i = self.weights.gate(graph, &gates, 0);
+        let i = graph.sigmoid::<E, F>(&i);
+        let f = self.weights.gate(graph, &gates, 1);
+        let f = graph.sigmoid::<E, F>(&f);
+        let g = self.weights.gate(graph, &gates, 2);
+        let g = graph.tanh::<E, F>(&g);
+        let o = self.weights.gate(graph, &gates, 3);
+        let o = graph.sigmoid::<E, F>(&o);
+
+        //c' = f * c + i * g
+        let kept = graph.mul::<E, F>(&f, cell);
+        let added = graph.mul::<E, F>(&i, &g);
+        let next_cell = graph.add::<E, F>(&kept, &added);
+
+        //h' = o * tanh(c')
+        let activated = graph.tanh::<E, F>(&next_cell);
+        let next_hidden = graph.mul::<E, F>(&o, &activated);
+
+        (next_hidden, next_cell)
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.weights.parameters()
+    }
+}
+
+//input: (batches, input_size), hidden: (batches, hidden_size)
+//Gates are ordered reset, update, new
+pub struct GruCell<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    weights: GateWeights<T, E, F>,
+
+    phantom: PhantomData<(T, E, F)>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> GruCell<T, E, F> {
+    pub fn new(graph: &mut CompGraph<T>, weight_ih: Box<dyn EngineTensor<Unit = T>>, weight_hh: Box<dyn EngineTensor<Unit = T>>, bias: Option<(Box<dyn EngineTensor<Unit = T>>, Box<dyn EngineTensor<Unit = T>>)>) -> Result<Self, EngineError> {
+        Ok(Self {
+            weights: GateWeights::new(graph, 3, weight_ih, weight_hh, bias)?,
+
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.weights.input_size()
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.weights.hidden_size
+    }
+
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, hidden: &CompGraphTensor) -> CompGraphTensor {
+        let ih = self.weights.ih.forward(graph, input);
+        let hh = self.weights.hh.forward(graph, hidden);
+
+        let [r, z] = [0, 1].map(|gate| {
+            let ih_gate = self.weights.gate(graph, &ih, gate);
+            let hh_gate = self.weights.gate(graph, &hh, gate);
+            let pre = graph.add::<E, F>(&ih_gate, &hh_gate);
+
+            graph.sigmoid::<E, F>(&pre)
+        });
+
+        //The reset gate only scales the hidden projection (including its bias) of the new gate
+        let ih_new = self.weights.gate(graph, &ih, 2);
+        let hh_new = self.weights.gate(graph, &hh, 2);
+        let reset = graph.mul::<E, F>(&r, &hh_new);
+        let pre = graph.add::<E, F>(&ih_new, &reset);
+        let n = graph.tanh::<E, F>(&pre);
+
+        //h' = (1 - z) * n + z * h
+        let not_z = graph.sub_scalar_lh::<E, F>(T::one(), &z);
+        let new = graph.mul::<E, F>(&not_z, &n);
+        let kept = graph.mul::<E, F>(&z, hidden);
+
+        graph.add::<E, F>(&new, &kept)
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.weights.parameters()
+    }
+}
+
+//Splits input: (seq_len, batches, input_size) into each step: (batches, input_size), along with batches
+fn sequence_steps<T: UnitCompatible, E: Engine<T>>(graph: &mut CompGraph<T>, input: &CompGraphTensor) -> Result<(Vec<CompGraphTensor>, usize), ComputationGraphError> {
+    let shape = graph.shape(input)?;
+
+    err_if_incorrect_num_dimensions(&shape, 3)?;
+
+    let seq_len = shape.get(0).map_err(EngineError::from)?;
+    let batches = shape.get(1).map_err(EngineError::from)?;
+
+    let steps = (0..seq_len).map(|t| {
+        let step = graph.narrow::<E>(input, 0, t, 1);
+
+        graph.flatten::<E>(&step, 0, 1)
+    }).collect();
+
+    Ok((steps, batches))
+}
+
+//Given states: (1, batches, hidden_size) are flattened to (batches, hidden_size), missing states start as zeros
+fn initial_state<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>>(graph: &mut CompGraph<T>, state: Option<&CompGraphTensor>, batches: usize, hidden_size: usize) -> Result<CompGraphTensor, ComputationGraphError> {
+    match state {
+        Some(state) => {
+            let shape = graph.shape(state)?;
+            let expected = shape![1, batches, hidden_size];
+
+            if shape != expected {
+                return Err(EngineError::ShapeMismatch(shape, expected).into());
+            }
+
+            Ok(graph.flatten::<E>(state, 0, 1))
+        },
+        None => Ok(graph.create_root(F::from_iter(iter::repeat(T::zero()).take(batches * hidden_size), shape![batches, hidden_size]).generic())),
+    }
+}
+
+//input: (seq_len, batches, input_size), out: (seq_len, batches, hidden_size)
+//Initial and final states: (1, batches, hidden_size), missing initial states start as zeros
+pub struct Rnn<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    cell: RnnCell<T, E, F>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Rnn<T, E, F> {
+    pub fn new(cell: RnnCell<T, E, F>) -> Self {
+        Self {
+            cell,
+        }
+    }
+
+    pub fn cell(&self) -> &RnnCell<T, E, F> {
+        &self.cell
+    }
+
+    //The shape of input has to be inferable to unroll it, returns (out, final hidden)
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, hidden: Option<&CompGraphTensor>) -> Result<(CompGraphTensor, CompGraphTensor), ComputationGraphError> {
+        let (steps, batches) = sequence_steps::<T, E>(graph, input)?;
+
+        let mut hidden = initial_state::<T, E, F>(graph, hidden, batches, self.cell.hidden_size())?;
+        let mut outputs = Vec::with_capacity(steps.len());
+
+        for step in steps {
+            hidden = self.cell.forward(graph, &step, &hidden);
+            outputs.push(hidden.clone());
+        }
+
+        let out = graph.stack::<E, F>(&outputs.iter().collect::<Vec<_>>(), 0);
+
+        Ok((out, graph.stack::<E, F>(&[&hidden], 0)))
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.cell.parameters()
+    }
+}
+
+//See Rnn, the cell state has the same shape as the hidden state
+pub struct Lstm<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    cell: LstmCell<T, E, F>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Lstm<T, E, F> {
+    pub fn new(cell: LstmCell<T, E, F>) -> Self {
+        Self {
+            cell,
+        }
+    }
+
+    pub fn cell(&self) -> &LstmCell<T, E, F> {
+        &self.cell
+    }
+
+    //Returns (out, (final hidden, final cell))
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, state: Option<(&CompGraphTensor, &CompGraphTensor)>) -> Result<(CompGraphTensor, (CompGraphTensor, CompGraphTensor)), ComputationGraphError> {
+        let (steps, batches) = sequence_steps::<T, E>(graph, input)?;
+        let (hidden, cell) = state.unzip();
+
+        let mut hidden = initial_state::<T, E, F>(graph, hidden, batches, self.cell.hidden_size())?;
+        let mut cell = initial_state::<T, E, F>(graph, cell, batches, self.cell.hidden_size())?;
+        let mut outputs = Vec::with_capacity(steps.len());
+
+        for step in steps {
+            (hidden, cell) = self.cell.forward(graph, &step, &hidden, &cell);
+            outputs.push(hidden.clone());
+        }
+
+        let out = graph.stack::<E, F>(&outputs.iter().collect::<Vec<_>>(), 0);
+
+        Ok((out, (graph.stack::<E, F>(&[&hidden], 0), graph.stack::<E, F>(&[&cell], 0))))
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.cell.parameters()
+    }
+}
+
+//See Rnn
+pub struct Gru<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> {
+    cell: GruCell<T, E, F>,
+}
+
+impl<T: UnitCompatible, E: Engine<T>, F: EngineTensorFactory<Unit = T>> Gru<T, E, F> {
+    pub fn new(cell: GruCell<T, E, F>) -> Self {
+        Self {
+            cell,
+        }
+    }
+
+    pub fn cell(&self) -> &GruCell<T, E, F> {
+        &self.cell
+    }
+
+    //Returns (out, final hidden)
+    pub fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor, hidden: Option<&CompGraphTensor>) -> Result<(CompGraphTensor, CompGraphTensor), ComputationGraphError> {
+        let (steps, batches) = sequence_steps::<T, E>(graph, input)?;
+
+        let mut hidden = initial_state::<T, E, F>(graph, hidden, batches, self.cell.hidden_size())?;
+        let mut outputs = Vec::with_capacity(steps.len());
+
+        for step in steps {
+            hidden = self.cell.forward(graph, &step, &hidden);
+            outputs.push(hidden.clone());
+        }
+
+        let out = graph.stack::<E, F>(&outputs.iter().collect::<Vec<_>>(), 0);
+
+        Ok((out, graph.stack::<E, F>(&[&hidden], 0)))
+    }
+
+    pub fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.cell.parameters()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine_impl::{basic::Basic, tensor::array::Array};
+
+    use super::*;
+
+    const WEIGHT_IH: [f64; 16] = [0.1, -0.2, 0.3, 0.4, -0.5, 0.6, 0.7, -0.8, 0.2, 0.1, -0.3, 0.5, 0.4, -0.6, 0.05, 0.15];
+    const WEIGHT_HH: [f64; 16] = [0.2, -0.1, 0.05, 0.3, -0.4, 0.2, 0.1, 0.1, 0.3, -0.2, 0.25, 0., -0.15, 0.35, 0.45, -0.05];
+    const BIAS_IH: [f64; 8] = [0.1, -0.1, 0.2, 0., 0.05, -0.05, 0.15, -0.2];
+    const BIAS_HH: [f64; 8] = [0., 0.1, -0.2, 0.3, 0.1, 0., -0.1, 0.05];
+
+    const INPUT: [f64; 6] = [0.5, -1., 1.5, 0.25, -0.75, 0.5];
+
+    //The first gates of the shared weights, hidden_size 2
+    fn weights(gates: usize) -> (Box<dyn EngineTensor<Unit = f64>>, Box<dyn EngineTensor<Unit = f64>>, Option<(Box<dyn EngineTensor<Unit = f64>>, Box<dyn EngineTensor<Unit = f64>>)>) {
+        let rows = gates * 2;
+
+        (
+            Array::from_slice(&WEIGHT_IH[..(rows * 2)], shape![rows, 2]).generic(),
+            Array::from_slice(&WEIGHT_HH[..(rows * 2)], shape![rows, 2]).generic(),
+            Some((Array::from_slice(&BIAS_IH[..rows], shape![rows]).generic(), Array::from_slice(&BIAS_HH[..rows], shape![rows]).generic())),
+        )
+    }
+
+    fn assert_close(graph: &CompGraph<f64>, res: &CompGraphTensor, expected: &[f64]) {
+        for (res_element, expected_element) in graph.iter(res).zip(expected.iter()) {
+            assert!((res_element - expected_element).abs() < 1e-12, "{res_element} != {expected_element}");
+        }
+    }
+
+    //Expected values in these tests are from a plain Python implementation of PyTorch's documented equations
+
+    #[test]
+    fn lstm_sequence() {
+        let mut graph = CompGraph::<f64>::new();
+
+        let (weight_ih, weight_hh, bias) = weights(4);
+        let lstm = Lstm::new(LstmCell::<f64, Basic, Array<f64>>::new(&mut graph, weight_ih, weight_hh, bias).unwrap());
+
+        assert_eq!(lstm.parameters().len(), 4);
+
+        let input = graph.create_root(Array::from_slice(&INPUT, shape![3, 1, 2]).generic());
+        let (out, (hidden, cell)) = lstm.forward(&mut graph, &input, None).unwrap();
+
+        for tensor in [&out, &hidden, &cell] {
+            graph.non_populating_eval(tensor).unwrap();
+        }
+
+        assert_eq!(graph.shape(&out).unwrap(), shape![3, 1, 2]);
+        assert_eq!(graph.shape(&cell).unwrap(), shape![1, 1, 2]);
+
+        assert_close(&graph, &out, &[0.06103143370331638, -0.11163201914540595, 0.17440211993071097, -0.19678149607662584, 0.08650238289015395, 0.032120108008078234]);
+        assert_close(&graph, &hidden, &[0.08650238289015395, 0.032120108008078234]);
+        assert_close(&graph, &cell, &[0.2568857629557352, 0.06511823154745067]);
+    }
+
+    #[test]
+    fn gru_sequence() {
+        let mut graph = CompGraph::<f64>::new();
+
+        let (weight_ih, weight_hh, bias) = weights(3);
+        let gru = Gru::new(GruCell::<f64, Basic, Array<f64>>::new(&mut graph, weight_ih, weight_hh, bias).unwrap());
+
+        let input = graph.create_root(Array::from_slice(&INPUT, shape![3, 1, 2]).generic());
+        let hidden = graph.create_root(Array::from_slice(&[0.1, -0.2], shape![1, 1, 2]).generic());
+
+        let (out, _) = gru.forward(&mut graph, &input, Some(&hidden)).unwrap();
+        graph.non_populating_eval(&out).unwrap();
+
+        assert_close(&graph, &out, &[0.13601330909930837, -0.27615670995898867, 0.3468798510128651, -0.291663717110232, 0.2457107056332772, 0.1808727793049275]);
+
+        //States need the leading num_layers dim
+        let flat = graph.create_root(Array::from_slice(&[0.1, -0.2], shape![1, 2]).generic());
+        let stacked = graph.create_root(Array::from_slice(&[0.1, -0.2, 0.3, 0.4], shape![2, 1, 2]).generic());
+
+        assert!(matches!(gru.forward(&mut graph, &input, Some(&flat)), Err(ComputationGraphError::ComputationError(EngineError::ShapeMismatch(_, _)))));
+        assert!(matches!(gru.forward(&mut graph, &input, Some(&stacked)), Err(ComputationGraphError::ComputationError(EngineError::ShapeMismatch(_, _)))));
+    }
+
+    #[test]
+    fn relu_cell_without_bias() {
+        let mut graph = CompGraph::<f64>::new();
+
+        let (weight_ih, weight_hh, _) = weights(1);
+        let cell = RnnCell::<f64, Basic, Array<f64>>::new(&mut graph, weight_ih, weight_hh, None, Nonlinearity::Relu).unwrap();
+
+        assert_eq!(cell.parameters().len(), 2);
+
+        let input = graph.create_root(Array::from_slice(&INPUT[..2], shape![1, 2]).generic());
+        let hidden = graph.create_root(Array::from_slice(&[0.5, -1.], shape![1, 2]).generic());
+
+        let out = cell.forward(&mut graph, &input, &hidden);
+        graph.non_populating_eval(&out).unwrap();
+
+        //Hand computed: 0.25 + 0.2 and -0.25 - 0.275 before the relu
+        assert_close(&graph, &out, &[0.45, 0.]);
+
+        let (weight_ih, _, _) = weights(1);
+        assert!(RnnCell::<f64, Basic, Array<f64>>::new(&mut graph, weight_ih, Array::from_slice(&[1., 0., 0.], shape![3, 1]).generic(), None, Nonlinearity::Tanh).is_err());
+    }
+}
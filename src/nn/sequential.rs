@@ -0,0 +1,81 @@
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::unit::UnitCompatible};
+
+use super::Layer;
+
+//Applies each layer to the output of the previous one
+pub struct Sequential<T: UnitCompatible> {
+    layers: Vec<Box<dyn Layer<T>>>,
+}
+
+impl<T: UnitCompatible> Sequential<T> {
+    pub fn new(layers: Vec<Box<dyn Layer<T>>>) -> Self {
+        Self {
+            layers,
+        }
+    }
+
+    pub fn push(&mut self, layer: Box<dyn Layer<T>>) {
+        self.layers.push(layer);
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl<T: UnitCompatible> Layer<T> for Sequential<T> {
+    fn forward(&self, graph: &mut CompGraph<T>, input: &CompGraphTensor) -> CompGraphTensor {
+        self.layers.iter().fold(input.clone(), |curr, layer| layer.forward(graph, &curr))
+    }
+
+    fn parameters(&self) -> Vec<CompGraphTensor> {
+        self.layers.iter().flat_map(|layer| layer.parameters()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine::{tensor::factory::EngineTensorFactory, ConvParams}, engine_impl::{basic::Basic, tensor::array::Array}, helper::{shape, Shape}, nn::{activation::Relu, conv::Conv2d, linear::Linear}};
+
+    use super::*;
+
+    #[test]
+    fn linear_relu_linear() {
+        let mut graph = CompGraph::<f32>::new();
+
+        let l1 = Linear::<f32, Basic, Array<f32>>::new(&mut graph, Array::from_slice(&[1., -1., -1., 1.], shape![2, 2]).generic(), Some(Array::from_slice(&[0., 1.], shape![2]).generic())).unwrap();
+        let l2 = Linear::<f32, Basic, Array<f32>>::new(&mut graph, Array::from_slice(&[2., 3.], shape![1, 2]).generic(), None).unwrap();
+
+        let model = Sequential::new(vec![Box::new(l1), Box::new(Relu::<f32, Basic, Array<f32>>::new()), Box::new(l2)]);
+
+        assert_eq!(model.parameters().len(), 3);
+
+        let input = graph.create_root(Array::from_slice(&[1., 3., 4., 2.], shape![2, 2]).generic());
+        let out = model.forward(&mut graph, &input);
+
+        assert_eq!(graph.shape(&out).unwrap(), shape![2, 1]);
+
+        graph.non_populating_eval(&out).unwrap();
+
+        //relu([[-2, 3], [2, -1]]) = [[0, 3], [2, 0]]
+        let expected = Array::from_slice(&[9., 4.], shape![2, 1]).generic();
+
+        assert_eq!(*Array::from_iter(graph.iter(&out), shape![2, 1]).generic(), *expected);
+    }
+
+    #[test]
+    fn conv_shape() {
+        let mut graph = CompGraph::<f32>::new();
+
+        let conv = Conv2d::<f32, Basic, Array<f32>>::new(&mut graph, Array::from_iter((0..(4 * 3 * 3 * 3)).map(|x| x as f32), shape![4, 3, 3, 3]).generic(), None, ConvParams::new([2, 2], [1, 1], [1, 1], 1)).unwrap();
+
+        let input = graph.create_root(Array::from_iter((0..(2 * 3 * 8 * 8)).map(|x| x as f32), shape![2, 3, 8, 8]).generic());
+        let out = Sequential::new(vec![Box::new(conv)]).forward(&mut graph, &input);
+
+        assert_eq!(graph.shape(&out).unwrap(), shape![2, 4, 4, 4]);
+    }
+}
@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible}};
+
+use super::{err_if_grads_mismatch, err_if_invalid_state, Optimizer, OptimizerError, OptimizerState, StateTensor};
+
+//Adam following the PyTorch formulation
+//Weight decay is added to the gradient (L2) unless decoupled, in which case the parameter is decayed directly (AdamW)
+pub struct Adam<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> {
+    params: Vec<CompGraphTensor>,
+
+    lr: f64,
+    betas: (f64, f64),
+    eps: f64,
+    weight_decay: f64,
+    decoupled_weight_decay: bool,
+
+    steps: usize,
+    exp_avgs: Vec<Option<Box<dyn EngineTensor<Unit = T>>>>,
+    exp_avg_sqs: Vec<Option<Box<dyn EngineTensor<Unit = T>>>>,
+
+    phantom: PhantomData<F>,
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> Adam<T, F> {
+    pub fn new(params: Vec<CompGraphTensor>, lr: f64, betas: (f64, f64), eps: f64, weight_decay: f64) -> Self {
+        Self::with_weight_decay_mode(params, lr, betas, eps, weight_decay, false)
+    }
+
+    fn with_weight_decay_mode(params: Vec<CompGraphTensor>, lr: f64, betas: (f64, f64), eps: f64, weight_decay: f64, decoupled_weight_decay: bool) -> Self {
+        let exp_avgs = params.iter().map(|_| None).collect();
+        let exp_avg_sqs = params.iter().map(|_| None).collect();
+
+        Self {
+            params,
+
+            lr,
+            betas,
+            eps,
+            weight_decay,
+            decoupled_weight_decay,
+
+            steps: 0,
+            exp_avgs,
+            exp_avg_sqs,
+
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> Optimizer<T> for Adam<T, F> {
+    fn step(&mut self, graph: &mut CompGraph<T>, grads: &[&dyn EngineTensor<Unit = T>]) -> Result<(), OptimizerError> {
+        err_if_grads_mismatch(graph, &self.params, grads)?;
+
+        let (beta1, beta2) = self.betas;
+        let step = self.steps + 1;
+
+        let bias_correction1 = 1.0 - beta1.powi(step as i32);
+        let bias_correction2 = 1.0 - beta2.powi(step as i32);
+
+        let eps = T::one().scale_double(self.eps);
+
+        for (((param, grad), exp_avg), exp_avg_sq) in self.params.iter().zip(grads.iter()).zip(self.exp_avgs.iter_mut()).zip(self.exp_avg_sqs.iter_mut()) {
+            let p = graph.tensor(param)?;
+
+            let (p_units, g): (Vec<T>, Vec<T>) = if self.decoupled_weight_decay {
+                p.iter_units().zip(grad.iter_units()).map(|(p, g)| (p.scale_double(1.0 - self.lr * self.weight_decay), g)).unzip()
+            } else {
+                p.iter_units().zip(grad.iter_units()).map(|(p, g)| (p, g + p.scale_double(self.weight_decay))).unzip()
+            };
+
+            let m = match exp_avg.as_ref() {
+                Some(m) => m.iter_units().zip(g.iter()).map(|(m, g)| m.scale_double(beta1) + g.scale_double(1.0 - beta1)).collect::<Vec<T>>(),
+                None => g.iter().map(|g| g.scale_double(1.0 - beta1)).collect(),
+            };
+
+            let v = match exp_avg_sq.as_ref() {
+                Some(v) => v.iter_units().zip(g.iter()).map(|(v, g)| v.scale_double(beta2) + (*g * *g).scale_double(1.0 - beta2)).collect::<Vec<T>>(),
+                None => g.iter().map(|g| (*g * *g).scale_double(1.0 - beta2)).collect(),
+            };
+
+            let updated = p_units.iter().zip(m.iter()).zip(v.iter()).map(|((p, m), v)| {
+                let denom = v.scale_double(1.0 / bias_correction2).sqrt() + eps;
+
+                *p - (m.scale_double(self.lr / bias_correction1) / denom)
+            });
+
+            let updated = F::from_iter(updated, p.shape().clone()).generic();
+
+            *exp_avg = Some(F::from_slice(&m, p.shape().clone()).generic());
+            *exp_avg_sq = Some(F::from_slice(&v, p.shape().clone()).generic());
+
+            graph.replace_root(param, updated)?;
+        }
+
+        self.steps = step;
+
+        Ok(())
+    }
+
+    fn state(&self) -> OptimizerState<T> {
+        let exp_avgs = self.exp_avgs.iter().enumerate().filter_map(|(i, t)| t.as_ref().map(|t| StateTensor::from_tensor("exp_avg", i, t.as_ref())));
+        let exp_avg_sqs = self.exp_avg_sqs.iter().enumerate().filter_map(|(i, t)| t.as_ref().map(|t| StateTensor::from_tensor("exp_avg_sq", i, t.as_ref())));
+
+        OptimizerState {
+            steps: self.steps,
+            tensors: exp_avgs.chain(exp_avg_sqs).collect(),
+        }
+    }
+
+    fn load_state(&mut self, graph: &CompGraph<T>, state: &OptimizerState<T>) -> Result<(), OptimizerError> {
+        err_if_invalid_state(graph, &self.params, &["exp_avg", "exp_avg_sq"], state)?;
+
+        self.steps = state.steps;
+        self.exp_avgs = (0..self.params.len()).map(|i| state.get("exp_avg", i).map(|t| F::from_slice(&t.data, t.shape.clone()).generic())).collect();
+        self.exp_avg_sqs = (0..self.params.len()).map(|i| state.get("exp_avg_sq", i).map(|t| F::from_slice(&t.data, t.shape.clone()).generic())).collect();
+
+        Ok(())
+    }
+}
+
+//Adam with decoupled weight decay
+pub struct AdamW<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> {
+    adam: Adam<T, F>,
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> AdamW<T, F> {
+    pub fn new(params: Vec<CompGraphTensor>, lr: f64, betas: (f64, f64), eps: f64, weight_decay: f64) -> Self {
+        Self {
+            adam: Adam::with_weight_decay_mode(params, lr, betas, eps, weight_decay, true),
+        }
+    }
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> Optimizer<T> for AdamW<T, F> {
+    fn step(&mut self, graph: &mut CompGraph<T>, grads: &[&dyn EngineTensor<Unit = T>]) -> Result<(), OptimizerError> {
+        self.adam.step(graph, grads)
+    }
+
+    fn state(&self) -> OptimizerState<T> {
+        self.adam.state()
+    }
+
+    fn load_state(&mut self, graph: &CompGraph<T>, state: &OptimizerState<T>) -> Result<(), OptimizerError> {
+        self.adam.load_state(graph, state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine::EngineError, engine_impl::tensor::array::Array, helper::{shape, Shape}};
+
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+        }
+    }
+
+    #[test]
+    fn first_step() {
+        let mut graph = CompGraph::<f64>::new();
+        let param = graph.create_root(Array::from_slice(&[1.0, 2.0], shape![2]).generic());
+
+        let grad = Array::from_slice(&[0.5, -2.0], shape![2]);
+
+        //The first bias corrected step moves each parameter by lr in the direction of the gradient
+        let mut adam = Adam::<f64, Array<f64>>::new(vec![param.clone()], 0.1, (0.9, 0.999), 0.0, 0.0);
+        adam.step(&mut graph, &[&grad]).unwrap();
+
+        assert_close(&graph.iter(&param).collect::<Vec<f64>>(), &[0.9, 2.1]);
+
+        let mut adamw = AdamW::<f64, Array<f64>>::new(vec![param.clone()], 0.1, (0.9, 0.999), 0.0, 0.5);
+        adamw.step(&mut graph, &[&grad]).unwrap();
+
+        assert_close(&graph.iter(&param).collect::<Vec<f64>>(), &[0.9 * 0.95 - 0.1, 2.1 * 0.95 + 0.1]);
+
+        let state = adamw.state();
+        assert_eq!(state.tensors.len(), 2);
+
+        let mut restored = AdamW::<f64, Array<f64>>::new(vec![param], 0.1, (0.9, 0.999), 0.0, 0.5);
+        restored.load_state(&graph, &state).unwrap();
+
+        assert_eq!(restored.state(), state);
+    }
+
+    #[test]
+    fn bad_grad_changes_nothing() {
+        let mut graph = CompGraph::<f64>::new();
+        let first = graph.create_root(Array::from_slice(&[1.0, 2.0], shape![2]).generic());
+        let second = graph.create_root(Array::from_slice(&[3.0], shape![1]).generic());
+
+        let mut adam = Adam::<f64, Array<f64>>::new(vec![first.clone(), second.clone()], 0.1, (0.9, 0.999), 0.0, 0.0);
+        adam.step(&mut graph, &[&Array::from_slice(&[0.5, -2.0], shape![2]), &Array::from_slice(&[1.0], shape![1])]).unwrap();
+
+        let params = (graph.iter(&first).collect::<Vec<f64>>(), graph.iter(&second).collect::<Vec<f64>>());
+        let state = adam.state();
+
+        //Only the last gradient is wrong, the first parameter and its moments still have to be left alone
+        let res = adam.step(&mut graph, &[&Array::from_slice(&[0.5, -2.0], shape![2]), &Array::from_slice(&[1.0, 1.0], shape![2])]);
+        assert!(matches!(res, Err(OptimizerError::Engine(EngineError::ShapeMismatch(_, _)))));
+
+        assert_eq!((graph.iter(&first).collect::<Vec<f64>>(), graph.iter(&second).collect::<Vec<f64>>()), params);
+        assert_eq!(adam.state(), state);
+    }
+}
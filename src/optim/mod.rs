@@ -0,0 +1,162 @@
+pub mod adam;
+pub mod sgd;
+
+use std::{fmt::{self, Display}, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor, ComputationGraphError}, engine::{tensor::EngineTensor, unit::UnitCompatible, EngineError}, helper::Shape};
+
+//Optimizers update parameter roots in a graph given a gradient for each parameter
+//Gradients are given in the same order as the parameters the optimizer was created with
+pub trait Optimizer<T: UnitCompatible> {
+    fn step(&mut self, graph: &mut CompGraph<T>, grads: &[&dyn EngineTensor<Unit = T>]) -> Result<(), OptimizerError>;
+
+    fn state(&self) -> OptimizerState<T>;
+    //Every state tensor must belong to one of the parameters and have its shape in graph
+    fn load_state(&mut self, graph: &CompGraph<T>, state: &OptimizerState<T>) -> Result<(), OptimizerError>;
+}
+
+//Plain data copy of everything an optimizer tracks between steps so it can be stored and loaded later
+//Written as text with to_string and read back with parse, see the Display impl for the format
+#[derive(Debug, Clone, PartialEq)]
+pub struct OptimizerState<T: UnitCompatible> {
+    pub steps: usize,
+    pub tensors: Vec<StateTensor<T>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTensor<T: UnitCompatible> {
+    pub name: String,
+    pub param_index: usize,
+    pub shape: Shape,
+    pub data: Vec<T>,
+}
+
+impl<T: UnitCompatible> StateTensor<T> {
+    pub fn from_tensor(name: &str, param_index: usize, tensor: &dyn EngineTensor<Unit = T>) -> Self {
+        Self {
+            name: name.to_string(),
+            param_index,
+            shape: tensor.shape().clone(),
+            data: tensor.iter_units().collect(),
+        }
+    }
+}
+
+impl<T: UnitCompatible> OptimizerState<T> {
+    pub fn get(&self, name: &str, param_index: usize) -> Option<&StateTensor<T>> {
+        self.tensors.iter().find(|t| t.name == name && t.param_index == param_index)
+    }
+}
+
+//A line with the step count then a line per state tensor: name, param index, shape and its units
+//Units are written with Display, which round trips floats exactly
+impl<T: UnitCompatible + Display> Display for OptimizerState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "steps {}", self.steps)?;
+
+        for tensor in self.tensors.iter() {
+            write!(f, "{} {} {}", tensor.name, tensor.param_index, tensor.shape)?;
+
+            for unit in tensor.data.iter() {
+                write!(f, " {unit}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: UnitCompatible + FromStr> FromStr for OptimizerState<T> {
+    type Err = OptimizerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+
+        let steps = lines.next().and_then(|line| line.strip_prefix("steps ")).and_then(|steps| steps.parse().ok());
+        let steps = steps.ok_or_else(|| OptimizerError::InvalidState(String::from("missing step count")))?;
+
+        let tensors = lines.filter(|line| !line.is_empty()).map(parse_state_tensor).collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            steps,
+            tensors,
+        })
+    }
+}
+
+fn parse_state_tensor<T: UnitCompatible + FromStr>(line: &str) -> Result<StateTensor<T>, OptimizerError> {
+    let invalid = || OptimizerError::InvalidState(format!("malformed state tensor \"{line}\""));
+    let mut fields = line.split_whitespace();
+
+    let name = fields.next().ok_or_else(invalid)?.to_string();
+    let param_index = fields.next().and_then(|i| i.parse().ok()).ok_or_else(invalid)?;
+
+    let dims = fields.next().and_then(|s| s.strip_prefix('(')).and_then(|s| s.strip_suffix(')')).ok_or_else(invalid)?;
+    let dims = dims.split(',').filter(|d| !d.is_empty()).map(|d| d.parse::<usize>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+
+    let data = fields.map(|unit| unit.parse::<T>()).collect::<Result<Vec<_>, _>>().map_err(|_| invalid())?;
+
+    Ok(StateTensor {
+        name,
+        param_index,
+        shape: Shape::from(dims.as_slice()),
+        data,
+    })
+}
+
+//Every state tensor has to be one of names, for one of the parameters and have the shape of that parameter
+fn err_if_invalid_state<T: UnitCompatible>(graph: &CompGraph<T>, params: &[CompGraphTensor], names: &[&str], state: &OptimizerState<T>) -> Result<(), OptimizerError> {
+    for tensor in state.tensors.iter() {
+        let param = params.get(tensor.param_index).filter(|_| names.contains(&tensor.name.as_str()));
+        let param = param.ok_or_else(|| OptimizerError::UnknownStateTensor(tensor.name.clone(), tensor.param_index))?;
+
+        let param_shape = graph.tensor(param)?.shape();
+
+        if tensor.shape != *param_shape {
+            return Err(OptimizerError::StateShapeMismatch(tensor.name.clone(), tensor.param_index, tensor.shape.clone(), param_shape.clone()));
+        }
+
+        if tensor.data.len() != tensor.shape.elements() {
+            return Err(OptimizerError::InvalidState(format!("state tensor {} for parameter {} has {} units but shape {}", tensor.name, tensor.param_index, tensor.data.len(), tensor.shape)));
+        }
+    }
+
+    Ok(())
+}
+
+//Every gradient is checked against its parameter up front so a bad one can't leave a step half applied
+fn err_if_grads_mismatch<T: UnitCompatible>(graph: &CompGraph<T>, params: &[CompGraphTensor], grads: &[&dyn EngineTensor<Unit = T>]) -> Result<(), OptimizerError> {
+    if grads.len() != params.len() {
+        return Err(OptimizerError::GradientCountMismatch(grads.len(), params.len()));
+    }
+
+    for (param, grad) in params.iter().zip(grads.iter()) {
+        let param_shape = graph.tensor(param)?.shape();
+
+        if grad.shape() != param_shape {
+            return Err(EngineError::ShapeMismatch(grad.shape().clone(), param_shape.clone()).into());
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Error, Debug)]
+pub enum OptimizerError {
+    #[error("Got {0} gradients but expected {1}")]
+    GradientCountMismatch(usize, usize),
+    #[error("State tensor {0} for parameter {1} does not belong to this optimizer")]
+    UnknownStateTensor(String, usize),
+    #[error("State tensor {0} for parameter {1} has shape {2} but the parameter has shape {3}")]
+    StateShapeMismatch(String, usize, Shape, Shape),
+    #[error("Invalid optimizer state: {0}")]
+    InvalidState(String),
+    #[error("Error in graph: {0}")]
+    ComputationGraph(#[from] ComputationGraphError),
+    #[error("Error in computation: {0}")]
+    Engine(#[from] EngineError),
+}
@@ -0,0 +1,159 @@
+use std::marker::PhantomData;
+
+use crate::{comp_graph::{CompGraph, CompGraphTensor}, engine::{tensor::{factory::EngineTensorFactory, EngineTensor}, unit::UnitCompatible}};
+
+use super::{err_if_grads_mismatch, err_if_invalid_state, Optimizer, OptimizerError, OptimizerState, StateTensor};
+
+//Stochastic gradient descent following the PyTorch formulation
+//g = grad + weight_decay * p
+//buf = momentum * buf + (1 - dampening) * g (buf = g on the first step)
+//g = g + momentum * buf if nesterov otherwise buf
+//p = p - lr * g
+pub struct Sgd<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> {
+    params: Vec<CompGraphTensor>,
+
+    lr: f64,
+    momentum: f64,
+    dampening: f64,
+    weight_decay: f64,
+    nesterov: bool,
+
+    steps: usize,
+    momentum_buffers: Vec<Option<Box<dyn EngineTensor<Unit = T>>>>,
+
+    phantom: PhantomData<F>,
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> Sgd<T, F> {
+    pub fn new(params: Vec<CompGraphTensor>, lr: f64, momentum: f64, dampening: f64, weight_decay: f64, nesterov: bool) -> Self {
+        let momentum_buffers = params.iter().map(|_| None).collect();
+
+        Self {
+            params,
+
+            lr,
+            momentum,
+            dampening,
+            weight_decay,
+            nesterov,
+
+            steps: 0,
+            momentum_buffers,
+
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<T: UnitCompatible, F: EngineTensorFactory<Unit = T>> Optimizer<T> for Sgd<T, F> {
+    fn step(&mut self, graph: &mut CompGraph<T>, grads: &[&dyn EngineTensor<Unit = T>]) -> Result<(), OptimizerError> {
+        err_if_grads_mismatch(graph, &self.params, grads)?;
+
+        for ((param, grad), momentum_buffer) in self.params.iter().zip(grads.iter()).zip(self.momentum_buffers.iter_mut()) {
+            let p = graph.tensor(param)?;
+
+            let mut g = p.iter_units().zip(grad.iter_units()).map(|(p, g)| g + p.scale_double(self.weight_decay)).collect::<Vec<T>>();
+
+            if self.momentum != 0.0 {
+                let buf = match momentum_buffer.as_ref() {
+                    Some(buf) => buf.iter_units().zip(g.iter()).map(|(b, g)| b.scale_double(self.momentum) + g.scale_double(1.0 - self.dampening)).collect::<Vec<T>>(),
+                    None => g.clone(),
+                };
+
+                g = if self.nesterov {
+                    g.iter().zip(buf.iter()).map(|(g, b)| *g + b.scale_double(self.momentum)).collect()
+                } else {
+                    buf.clone()
+                };
+
+                *momentum_buffer = Some(F::from_slice(&buf, p.shape().clone()).generic());
+            }
+
+            let updated = F::from_iter(p.iter_units().zip(g).map(|(p, g)| p - g.scale_double(self.lr)), p.shape().clone()).generic();
+
+            graph.replace_root(param, updated)?;
+        }
+
+        self.steps += 1;
+
+        Ok(())
+    }
+
+    fn state(&self) -> OptimizerState<T> {
+        OptimizerState {
+            steps: self.steps,
+            tensors: self.momentum_buffers.iter().enumerate().filter_map(|(i, buf)| buf.as_ref().map(|buf| StateTensor::from_tensor("momentum_buffer", i, buf.as_ref()))).collect(),
+        }
+    }
+
+    fn load_state(&mut self, graph: &CompGraph<T>, state: &OptimizerState<T>) -> Result<(), OptimizerError> {
+        err_if_invalid_state(graph, &self.params, &["momentum_buffer"], state)?;
+
+        self.steps = state.steps;
+        self.momentum_buffers = (0..self.params.len()).map(|i| state.get("momentum_buffer", i).map(|t| F::from_slice(&t.data, t.shape.clone()).generic())).collect();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine_impl::tensor::array::Array, helper::{shape, Shape}};
+
+    use super::*;
+
+    #[test]
+    fn momentum_and_state() {
+        let mut graph = CompGraph::<f64>::new();
+        let param = graph.create_root(Array::from_slice(&[1.0, 2.0], shape![2]).generic());
+
+        let grad = Array::from_slice(&[1.0, -1.0], shape![2]);
+
+        let mut sgd = Sgd::<f64, Array<f64>>::new(vec![param.clone()], 0.1, 0.9, 0.0, 0.0, false);
+
+        sgd.step(&mut graph, &[&grad]).unwrap();
+        sgd.step(&mut graph, &[&grad]).unwrap();
+
+        //buf = 1, then 1.9
+        assert_eq!(graph.iter(&param).collect::<Vec<f64>>(), vec![1.0 - 0.1 - 0.19, 2.0 + 0.1 + 0.19]);
+
+        let state = sgd.state();
+        assert_eq!(state.steps, 2);
+        assert_eq!(state.get("momentum_buffer", 0).unwrap().data, vec![1.9, -1.9]);
+
+        let mut restored = Sgd::<f64, Array<f64>>::new(vec![param.clone()], 0.1, 0.9, 0.0, 0.0, false);
+        restored.load_state(&graph, &state).unwrap();
+
+        assert_eq!(restored.state(), state);
+
+        //Stored as text and read back exactly
+        let text = state.to_string();
+        assert_eq!(text, "steps 2\nmomentum_buffer 0 (2) 1.9 -1.9\n");
+        assert_eq!(text.parse::<OptimizerState<f64>>().unwrap(), state);
+
+        assert!(matches!("steps two\n".parse::<OptimizerState<f64>>(), Err(OptimizerError::InvalidState(_))));
+        assert!(matches!("steps 2\nmomentum_buffer 0 (2 1.9 -1.9\n".parse::<OptimizerState<f64>>(), Err(OptimizerError::InvalidState(_))));
+        assert!(matches!("steps 2\nmomentum_buffer 0 (2) 1.9 x\n".parse::<OptimizerState<f64>>(), Err(OptimizerError::InvalidState(_))));
+    }
+
+    #[test]
+    fn load_state_checks_tensors() {
+        let mut graph = CompGraph::<f64>::new();
+        let param = graph.create_root(Array::from_slice(&[1.0, 2.0], shape![2]).generic());
+
+        let mut sgd = Sgd::<f64, Array<f64>>::new(vec![param], 0.1, 0.9, 0.0, 0.0, false);
+
+        let state = |name: &str, param_index: usize, shape: Shape, data: Vec<f64>| OptimizerState {
+            steps: 1,
+            tensors: vec![StateTensor { name: name.to_string(), param_index, shape, data }],
+        };
+
+        assert!(matches!(sgd.load_state(&graph, &state("exp_avg", 0, shape![2], vec![0., 0.])), Err(OptimizerError::UnknownStateTensor(_, 0))));
+        assert!(matches!(sgd.load_state(&graph, &state("momentum_buffer", 1, shape![2], vec![0., 0.])), Err(OptimizerError::UnknownStateTensor(_, 1))));
+        assert!(matches!(sgd.load_state(&graph, &state("momentum_buffer", 0, shape![1, 2], vec![0., 0.])), Err(OptimizerError::StateShapeMismatch(_, 0, _, _))));
+        assert!(matches!(sgd.load_state(&graph, &state("momentum_buffer", 0, shape![2], vec![0.])), Err(OptimizerError::InvalidState(_))));
+
+        //Nothing is loaded from a rejected state
+        assert_eq!(sgd.state().steps, 0);
+    }
+}